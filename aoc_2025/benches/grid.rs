@@ -0,0 +1,83 @@
+//! Times day 4 on generated grids: one accessibility pass scanning chars
+//! against the packed bit grid, and the per-cell removal worklist against
+//! bit grid rounds. Run with `cargo bench --bench grid`.
+
+mod common;
+
+use std::time::Instant;
+
+use aoc_2025::days::day_04::{BitGrid, Rule, peel};
+use common::Lcg;
+
+/// A square grid where roughly `density` percent of cells hold a roll.
+fn grid(rng: &mut Lcg, size: usize, density: u64) -> Vec<Vec<char>> {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.next(100) < density { '@' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+/// Part 1 the way it was first written: count the 8 neighbours of every roll.
+fn scan_accessible(grid: &[Vec<char>]) -> usize {
+    let (rows, cols) = (grid.len() as isize, grid[0].len() as isize);
+    let on_grid = |r: isize, c: isize| r >= 0 && r < rows && c >= 0 && c < cols;
+    let mut count = 0;
+    for r in 0..rows {
+        for c in 0..cols {
+            if grid[r as usize][c as usize] != '@' {
+                continue;
+            }
+            let adjacent = (-1..=1)
+                .flat_map(|dr| (-1..=1).map(move |dc| (r + dr, c + dc)))
+                .filter(|&(nr, nc)| (nr, nc) != (r, c) && on_grid(nr, nc))
+                .filter(|&(nr, nc)| grid[nr as usize][nc as usize] == '@')
+                .count();
+            if adjacent < 4 {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+fn main() {
+    let mut rng = Lcg(2025);
+    let rule = Rule::default();
+
+    for size in [500, 1000, 2000, 4000] {
+        let grid = grid(&mut rng, size, 70);
+
+        let start = Instant::now();
+        let scanned = scan_accessible(&grid);
+        let scan = start.elapsed();
+
+        let bits = BitGrid::from_grid(&grid);
+        let start = Instant::now();
+        let part1 = bits.accessible(&rule).count_ones();
+        let packed1 = start.elapsed();
+
+        let start = Instant::now();
+        let removal = peel(&bits, &rule);
+        let worklist = start.elapsed();
+
+        let start = Instant::now();
+        let per_round = bits.peel_rounds(&rule);
+        let packed2 = start.elapsed();
+
+        assert_eq!(part1, scanned);
+        assert_eq!(per_round, removal.per_round);
+        println!(
+            "{:>4}x{:<4} part 1: scan {:>9.2?}, bit grid {:>9.2?} | part 2: worklist {:>9.2?}, bit grid {:>9.2?} ({} rounds)",
+            size,
+            size,
+            scan,
+            packed1,
+            worklist,
+            packed2,
+            per_round.len()
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Times the integer program solver on generated day 10 style machines with
+//! increasing numbers of buttons. Run with `cargo bench --bench ilp`.
+
+mod common;
+
+use std::time::Instant;
+
+use aoc_2025::ilp::{IntegerProgram, Outcome};
+use aoc_2025::linalg::{Matrix, Rational};
+use common::Lcg;
+
+/// Build a feasible machine: random buttons over `counters` counters, with
+/// joltages produced by a random press vector.
+fn machine(rng: &mut Lcg, counters: usize, buttons: usize) -> IntegerProgram {
+    let mut a = Matrix::zeros(counters, buttons);
+    let mut joltages = vec![0i128; counters];
+    let mut wiring = vec![Vec::new(); buttons];
+
+    for (c, wires) in wiring.iter_mut().enumerate() {
+        let presses = rng.next(20) as i128;
+        for (r, joltage) in joltages.iter_mut().enumerate() {
+            if rng.next(3) == 0 || r == c % counters {
+                a.set(r, c, 1i128);
+                *joltage += presses;
+                wires.push(r);
+            }
+        }
+    }
+
+    let upper = wiring
+        .iter()
+        .map(|wires| wires.iter().map(|&r| joltages[r]).min())
+        .collect();
+    let b = joltages.into_iter().map(Rational::from).collect();
+
+    IntegerProgram::new(a, b, vec![Rational::ONE; buttons]).with_upper_bounds(upper)
+}
+
+fn main() {
+    let mut rng = Lcg(2025);
+    let counters = 10;
+
+    for buttons in [8, 13, 20, 30, 40] {
+        let programs: Vec<IntegerProgram> = (0..10)
+            .map(|_| machine(&mut rng, counters, buttons))
+            .collect();
+
+        let start = Instant::now();
+        let mut total = 0;
+        for program in &programs {
+            match program.solve() {
+                Outcome::Optimal(solution) => total += solution.objective.to_integer().unwrap(),
+                outcome => panic!("Generated machine has no optimum: {:?}", outcome),
+            }
+        }
+        let elapsed = start.elapsed();
+
+        println!(
+            "{:>2} buttons x {} counters: {:>10.2?} per machine (total presses {})",
+            buttons,
+            counters,
+            elapsed / programs.len() as u32,
+            total
+        );
+    }
+}
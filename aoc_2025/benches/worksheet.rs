@@ -0,0 +1,97 @@
+//! Times day 6 on generated worksheets that are a few rows tall but very
+//! wide: the original column scan calling `chars().nth(col)` per cell against
+//! the char grid behind `Worksheet`. Run with `cargo bench --bench worksheet`.
+
+mod common;
+
+use std::time::Instant;
+
+use aoc_2025::days::day_06::{ReadingMode, Worksheet};
+use common::Lcg;
+
+/// Four number rows and an operator row, at least `width` columns wide, made
+/// of problems one to three digits wide. Every tenth problem starts with an
+/// 'é' in its top row so columns and bytes disagree.
+fn worksheet(rng: &mut Lcg, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new(); 5];
+    let (mut problem, mut columns) = (0, 0);
+    while columns < width {
+        let digits = 1 + rng.next(3) as usize;
+        for line in &mut lines[..4] {
+            for _ in 0..digits {
+                line.push(char::from(b'0' + rng.next(10) as u8));
+            }
+        }
+        if problem % 10 == 0 {
+            lines[0].pop();
+            lines[0].push('é');
+        }
+        let op = if rng.next(2) == 0 { '+' } else { '*' };
+        lines[4].push(op);
+        lines[4].extend(std::iter::repeat_n(' ', digits - 1));
+        for line in &mut lines {
+            line.push(' ');
+        }
+        problem += 1;
+        columns += digits + 1;
+    }
+    lines
+}
+
+/// Count problems the way the worksheet was first scanned: one
+/// `chars().nth(col)` per row for every column, quadratic in the width.
+fn scan_problems(lines: &[String]) -> usize {
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let is_separator = |col: usize| {
+        lines
+            .iter()
+            .all(|line| line.chars().nth(col).unwrap_or(' ') == ' ')
+    };
+    let mut problems = 0;
+    let mut col = 0;
+    while col < width {
+        if is_separator(col) {
+            col += 1;
+            continue;
+        }
+        problems += 1;
+        while col < width && !is_separator(col) {
+            col += 1;
+        }
+    }
+    problems
+}
+
+fn main() {
+    let mut rng = Lcg(2025);
+
+    for width in [5_000, 20_000, 200_000, 2_000_000] {
+        let lines = worksheet(&mut rng, width);
+
+        // The nth scan is far too slow to run on the widest sheets
+        let scan = (width <= 20_000).then(|| {
+            let start = Instant::now();
+            let problems = scan_problems(&lines);
+            (problems, start.elapsed())
+        });
+
+        let start = Instant::now();
+        let sheet = Worksheet::parse(&lines).unwrap();
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let part1 = sheet.solve(ReadingMode::Horizontal).unwrap();
+        let part2 = sheet.solve(ReadingMode::VerticalTopDown).unwrap();
+        let solve = start.elapsed();
+
+        let problems = sheet.problems(ReadingMode::Horizontal).count();
+        if let Some((scanned, _)) = scan {
+            assert_eq!(scanned, problems);
+        }
+        let scan = scan.map_or("-".to_string(), |(_, time)| format!("{:.2?}", time));
+        println!(
+            "{:>9} columns, {:>6} problems: nth scan {:>9}, grid parse {:>9.2?}, solve both parts {:>9.2?} ({} / {})",
+            width, problems, scan, parse, solve, part1, part2
+        );
+    }
+}
@@ -1,91 +1,541 @@
-use crate::read_input;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use crate::parse::{Cursor, ParseError, parse_lines};
+use crate::{read_input, write_debug};
 
 pub fn run() {
     let input = read_input("day_01");
+    let rotations = parse_rotations(&input).unwrap();
 
     // Part 1
     // Dial points to 0-99, starts at 50
     // L = left (toward lower numbers), R = right (toward higher numbers)
     // Count how many times the dial points at 0 after any rotation
-    let mut position: i32 = 50;
-    let mut count_zeros_p1 = 0;
+    let res01 = count_hits(
+        &mut Dial::new(100, 50, &[0]),
+        &rotations,
+        CountMode::Landings,
+    );
 
-    for line in &input {
-        let direction = line.chars().next().unwrap();
-        let distance: i32 = line[1..].parse().unwrap();
+    // Part 2
+    // Count every click that causes the dial to point at 0 (during or at end of rotation)
+    let res02 = count_hits(
+        &mut Dial::new(100, 50, &[0]),
+        &rotations,
+        CountMode::Crossings,
+    );
 
-        match direction {
-            'L' => position -= distance,
-            'R' => position += distance,
-            _ => panic!("Unknown direction: {}", direction),
-        }
+    println!("Part 1 answer: {}", res01);
+    println!("Part 2 answer: {}", res02);
 
-        // Wrap around to 0-99 range
-        position = ((position % 100) + 100) % 100;
+    // Set AOC_DEBUG_DIR to dump the full trace and visit histogram as CSV
+    let dial = Dial::new(100, 50, &[0]);
+    write_debug("day_01_trace.csv", || {
+        trace_to_csv(&trace(&dial, &rotations))
+    });
+    write_debug("day_01_histogram.csv", || {
+        histogram(&dial, &rotations).to_csv()
+    });
 
-        if position == 0 {
-            count_zeros_p1 += 1;
+    // The input played backwards from where it ended, which must finish at the start
+    write_debug("day_01_replay.csv", || {
+        let mut end = dial.clone();
+        count_hits(&mut end, &rotations, CountMode::Landings);
+        trace_to_csv(&trace(&end, &inverse(&rotations)))
+    });
+}
+
+/// Which way the dial turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Toward lower numbers.
+    Left,
+    /// Toward higher numbers.
+    Right,
+}
+
+/// A single dial instruction such as `L68`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rotation {
+    dir: Direction,
+    distance: i64,
+}
+
+impl Rotation {
+    /// The rotation as a signed number of clicks (left is negative).
+    fn delta(&self) -> i64 {
+        match self.dir {
+            Direction::Left => -self.distance,
+            Direction::Right => self.distance,
         }
     }
 
-    let res01 = count_zeros_p1;
+    /// The rotation that undoes this one.
+    fn inverse(&self) -> Rotation {
+        let dir = match self.dir {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        };
+        Rotation { dir, ..*self }
+    }
+}
 
-    // Part 2
-    // Count every click that causes the dial to point at 0 (during or at end of rotation)
-    let mut position: i32 = 50;
-    let mut count_zeros_p2 = 0;
+/// Parse one rotation. Accepts `L68`/`R5` (a single letter in either case,
+/// optional space before the distance) or a plain signed integer where negative
+/// means left.
+fn parse_rotation(c: &mut Cursor) -> Result<Rotation, ParseError> {
+    let at_start = c.skip_ws().clone();
+    let dir = match c.peek().map(|ch| ch.to_ascii_uppercase()) {
+        Some('L') => Some(Direction::Left),
+        Some('R') => Some(Direction::Right),
+        _ => None,
+    };
 
-    for line in &input {
-        let direction = line.chars().next().unwrap();
-        let distance: i32 = line[1..].parse().unwrap();
+    let Some(dir) = dir else {
+        let delta: i64 = c.int()?;
+        let dir = if delta < 0 {
+            Direction::Left
+        } else {
+            Direction::Right
+        };
+        // -2^63 has no positive counterpart as a distance
+        let distance = delta
+            .checked_abs()
+            .ok_or_else(|| at_start.error(format!("distance {} is out of range", delta)))?;
+        return Ok(Rotation { dir, distance });
+    };
 
-        count_zeros_p2 += count_zeros_in_rotation(position, direction, distance);
+    c.bump();
+    c.skip_ws();
+    if !c.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+        return Err(c.error("expected a non-negative distance"));
+    }
+    Ok(Rotation {
+        dir,
+        distance: c.int()?,
+    })
+}
 
-        match direction {
-            'L' => position -= distance,
-            'R' => position += distance,
-            _ => panic!("Unknown direction: {}", direction),
-        }
+impl FromStr for Rotation {
+    type Err = ParseError;
 
-        position = ((position % 100) + 100) % 100;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut c = Cursor::new(s);
+        let rotation = parse_rotation(&mut c)?;
+        c.skip_ws().end()?;
+        Ok(rotation)
     }
+}
 
-    let res02 = count_zeros_p2;
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.dir {
+            Direction::Left => write!(f, "L{}", self.distance),
+            Direction::Right => write!(f, "R{}", self.distance),
+        }
+    }
+}
 
-    println!("Part 1 answer: {}", res01);
-    println!("Part 2 answer: {}", res02);
+/// Parse every non-empty input line as a rotation.
+fn parse_rotations(input: &[String]) -> Result<Vec<Rotation>, ParseError> {
+    parse_lines(input, parse_rotation)
 }
 
-/// Count how many times we pass through 0 during a rotation
-fn count_zeros_in_rotation(start: i32, direction: char, distance: i32) -> i32 {
-    match direction {
-        'L' => {
-            // Moving left (decreasing), we hit 0 at step: start, start+100, start+200, etc.
-            if start == 0 {
-                // From 0, we hit 0 again at steps 100, 200, etc.
-                distance / 100
-            } else if distance >= start {
-                // First hit at step `start`, then every 100 steps
-                1 + (distance - start) / 100
-            } else {
-                0
-            }
+/// The instructions that undo `rotations`: each one inverted, in reverse order.
+fn inverse(rotations: &[Rotation]) -> Vec<Rotation> {
+    rotations.iter().rev().map(Rotation::inverse).collect()
+}
+
+/// How a rotation scores against the dial's targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CountMode {
+    /// Only count a target the rotation ends on.
+    Landings,
+    /// Count every click that points at a target, during or at the end of a rotation.
+    Crossings,
+}
+
+/// A circular dial numbered `0..size` with one or more target values.
+#[derive(Debug, Clone)]
+struct Dial {
+    size: i64,
+    position: i64,
+    targets: Vec<i64>,
+}
+
+impl Dial {
+    /// Create a dial of the given size. The start and targets are taken modulo
+    /// the size; duplicate targets only count once.
+    fn new(size: i64, start: i64, targets: &[i64]) -> Self {
+        assert!(size > 0, "Dial size must be positive");
+        let mut targets: Vec<i64> = targets.iter().map(|t| t.rem_euclid(size)).collect();
+        targets.sort_unstable();
+        targets.dedup();
+
+        Dial {
+            size,
+            position: start.rem_euclid(size),
+            targets,
         }
-        'R' => {
-            // Moving right (increasing), we hit 0 at step: (100-start), (200-start), etc.
-            if start == 0 {
-                // From 0, we hit 0 again at steps 100, 200, etc.
-                distance / 100
-            } else {
-                let first_zero = 100 - start;
-                if distance >= first_zero {
-                    1 + (distance - first_zero) / 100
+    }
+
+    /// Turn the dial by `delta` clicks (negative = left) and return how many
+    /// target hits that scores under `mode`.
+    fn rotate(&mut self, delta: i64, mode: CountMode) -> u64 {
+        let crossings = self.crossings(delta);
+        self.position = self.advance(self.position, delta);
+
+        match mode {
+            CountMode::Crossings => crossings,
+            CountMode::Landings => self.targets.contains(&self.position) as u64,
+        }
+    }
+
+    /// Where `delta` clicks from `position` ends up. Worked out in i128, since
+    /// position + delta overflows i64 for distances near i64::MAX, and even
+    /// reduced first it does once the size is above i64::MAX / 2.
+    fn advance(&self, position: i64, delta: i64) -> i64 {
+        (position as i128 + delta as i128).rem_euclid(self.size as i128) as i64
+    }
+
+    /// Count the clicks in a rotation of `delta` that land on a target.
+    fn crossings(&self, delta: i64) -> u64 {
+        let distance = delta.abs();
+
+        self.targets
+            .iter()
+            .map(|&target| {
+                // Clicks until we first reach the target; from the target
+                // itself that's a full turn.
+                let gap = if delta >= 0 {
+                    target - self.position
+                } else {
+                    self.position - target
+                };
+                let first = match gap.rem_euclid(self.size) {
+                    0 => self.size,
+                    g => g,
+                };
+
+                if distance >= first {
+                    (1 + (distance - first) / self.size) as u64
                 } else {
                     0
                 }
+            })
+            .sum()
+    }
+}
+
+/// Apply every rotation to the dial and total the target hits.
+fn count_hits(dial: &mut Dial, rotations: &[Rotation], mode: CountMode) -> u64 {
+    rotations.iter().map(|r| dial.rotate(r.delta(), mode)).sum()
+}
+
+/// The dial's state around a single rotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TraceStep {
+    rotation: Rotation,
+    start: i64,
+    end: i64,
+    /// Clicks during the rotation that pointed at a target (including the last).
+    crossings: u64,
+    /// Whether the rotation ended on a target.
+    landed: bool,
+}
+
+/// Replay the rotations on a copy of the dial, recording every step.
+fn trace(dial: &Dial, rotations: &[Rotation]) -> Vec<TraceStep> {
+    let mut dial = dial.clone();
+
+    rotations
+        .iter()
+        .map(|&rotation| {
+            let start = dial.position;
+            let crossings = dial.rotate(rotation.delta(), CountMode::Crossings);
+            TraceStep {
+                rotation,
+                start,
+                end: dial.position,
+                crossings,
+                landed: dial.targets.contains(&dial.position),
             }
+        })
+        .collect()
+}
+
+/// Render a trace as CSV, one row per rotation (numbered from 1).
+fn trace_to_csv(trace: &[TraceStep]) -> String {
+    let mut csv = String::from("step,rotation,start,end,crossings,landed\n");
+    for (i, step) in trace.iter().enumerate() {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{}",
+            i + 1,
+            step.rotation,
+            step.start,
+            step.end,
+            step.crossings,
+            step.landed
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// How often each dial value was landed on or clicked past.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Histogram {
+    /// Rotations that ended on each value.
+    visits: Vec<u64>,
+    /// Clicks that pointed at each value, during or at the end of a rotation.
+    passes: Vec<u64>,
+}
+
+impl Histogram {
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("value,visits,passes\n");
+        for (value, (visits, passes)) in self.visits.iter().zip(&self.passes).enumerate() {
+            writeln!(csv, "{},{},{}", value, visits, passes).unwrap();
         }
-        _ => 0,
+        csv
+    }
+}
+
+/// Build the visit histogram for the rotations, starting from the dial's state.
+/// Full turns are added in bulk, so a rotation costs at most O(size).
+fn histogram(dial: &Dial, rotations: &[Rotation]) -> Histogram {
+    let size = dial.size as usize;
+    let mut visits = vec![0; size];
+    let mut passes = vec![0; size];
+    let mut full_turns = 0;
+    let mut position = dial.position;
+
+    for rotation in rotations {
+        let delta = rotation.delta();
+        full_turns += (delta.abs() / dial.size) as u64;
+        let step = delta.signum();
+        for k in 1..=(delta.abs() % dial.size) {
+            passes[dial.advance(position, step * k) as usize] += 1;
+        }
+
+        position = dial.advance(position, delta);
+        visits[position as usize] += 1;
     }
+
+    for p in &mut passes {
+        *p += full_turns;
+    }
+
+    Histogram { visits, passes }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<Rotation> {
+        let input: Vec<String> = [
+            "L68", "L30", "R48", "L5", "R60", "L55", "L1", "L99", "R14", "L82",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        parse_rotations(&input).unwrap()
+    }
+
+    #[test]
+    fn test_example() {
+        let rotations = example();
+        assert_eq!(
+            count_hits(
+                &mut Dial::new(100, 50, &[0]),
+                &rotations,
+                CountMode::Landings
+            ),
+            3
+        );
+        assert_eq!(
+            count_hits(
+                &mut Dial::new(100, 50, &[0]),
+                &rotations,
+                CountMode::Crossings
+            ),
+            6
+        );
+    }
+
+    #[test]
+    fn test_rotation_notations() {
+        let left = Rotation {
+            dir: Direction::Left,
+            distance: 68,
+        };
+        let right = left.inverse();
+
+        assert_eq!("L68".parse(), Ok(left));
+        assert_eq!("l68".parse(), Ok(left));
+        assert_eq!("  L 68 ".parse(), Ok(left));
+        assert_eq!("-68".parse(), Ok(left));
+        assert_eq!("r68".parse(), Ok(right));
+        assert_eq!("+68".parse(), Ok(right));
+        assert_eq!("68".parse(), Ok(right));
+        assert_eq!(left.to_string(), "L68");
+
+        let err = "X5".parse::<Rotation>().unwrap_err();
+        assert_eq!(err.column, 1);
+        let err = "L-5".parse::<Rotation>().unwrap_err();
+        assert_eq!(err.column, 2);
+        let err = "LR5".parse::<Rotation>().unwrap_err();
+        assert_eq!(err.column, 2);
+        let err = "Rubbish5".parse::<Rotation>().unwrap_err();
+        assert_eq!(err.column, 2);
+        let err = "-9223372036854775808".parse::<Rotation>().unwrap_err();
+        assert_eq!(err.column, 1);
+        assert_eq!(
+            "-9223372036854775807"
+                .parse::<Rotation>()
+                .map(|r| r.distance),
+            Ok(i64::MAX)
+        );
+        let err = parse_rotations(&["R1".to_string(), "Q2".to_string()]).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_replay_backwards_recovers_start() {
+        let rotations = example();
+        let mut dial = Dial::new(100, 50, &[0]);
+        count_hits(&mut dial, &rotations, CountMode::Landings);
+        assert_eq!(dial.position, 32);
+
+        count_hits(&mut dial, &inverse(&rotations), CountMode::Landings);
+        assert_eq!(dial.position, 50);
+    }
+
+    #[test]
+    fn test_trace() {
+        let rotations = example();
+        let steps = trace(&Dial::new(100, 50, &[0]), &rotations);
+
+        assert_eq!(steps.len(), rotations.len());
+        assert_eq!(
+            steps[0],
+            TraceStep {
+                rotation: Rotation {
+                    dir: Direction::Left,
+                    distance: 68,
+                },
+                start: 50,
+                end: 82,
+                crossings: 1,
+                landed: false,
+            }
+        );
+        assert_eq!(steps.iter().filter(|s| s.landed).count(), 3);
+        assert_eq!(steps.iter().map(|s| s.crossings).sum::<u64>(), 6);
+
+        let csv = trace_to_csv(&steps);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("step,rotation,start,end,crossings,landed")
+        );
+        assert_eq!(lines.next(), Some("1,L68,50,82,1,false"));
+        assert_eq!(lines.count(), 9);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let rotations = example();
+        let dial = Dial::new(100, 50, &[0]);
+        let hist = histogram(&dial, &rotations);
+
+        // Visits and passes at the target agree with the part answers
+        assert_eq!(hist.visits[0], 3);
+        assert_eq!(hist.passes[0], 6);
+        assert_eq!(hist.visits.iter().sum::<u64>(), rotations.len() as u64);
+        assert_eq!(
+            hist.passes.iter().sum::<u64>(),
+            rotations.iter().map(|r| r.distance as u64).sum::<u64>()
+        );
+
+        // A rotation of more than a full turn passes every value
+        let hist = histogram(&Dial::new(4, 0, &[0]), &["R9".parse().unwrap()]);
+        assert_eq!(hist.passes, vec![2, 3, 2, 2]);
+        assert_eq!(
+            hist.to_csv(),
+            "value,visits,passes\n0,0,2\n1,1,3\n2,0,2\n3,0,2\n"
+        );
+    }
+
+    #[test]
+    fn test_longest_rotations() {
+        // i64::MAX is 7 past a multiple of 100
+        let rotations: Vec<Rotation> = ["R9223372036854775807", "-9223372036854775807"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+
+        let mut dial = Dial::new(100, 50, &[0]);
+        dial.rotate(rotations[0].delta(), CountMode::Landings);
+        assert_eq!(dial.position, 57);
+        dial.rotate(rotations[1].delta(), CountMode::Landings);
+        assert_eq!(dial.position, 50);
+
+        let mut dial = Dial::new(100, 50, &[0]);
+        assert_eq!(
+            dial.rotate(-i64::MAX, CountMode::Crossings),
+            1 + (i64::MAX as u64 - 50) / 100
+        );
+        assert_eq!(dial.position, 43);
+
+        let hist = histogram(&Dial::new(100, 50, &[0]), &rotations);
+        assert_eq!(hist.visits[57] + hist.visits[50], 2);
+    }
+
+    #[test]
+    fn test_huge_dial() {
+        // Above i64::MAX / 2, position + delta overflows even after reducing
+        let mut dial = Dial::new(i64::MAX, i64::MAX - 1, &[0]);
+        assert_eq!(dial.rotate(i64::MAX - 1, CountMode::Crossings), 1);
+        assert_eq!(dial.position, i64::MAX - 2);
+        assert_eq!(dial.rotate(-(i64::MAX - 1), CountMode::Landings), 0);
+        assert_eq!(dial.position, i64::MAX - 1);
+    }
+
+    #[test]
+    fn test_crossings_match_brute_force() {
+        // Step one click at a time on a small dial with several targets
+        for size in 1..8 {
+            for start in 0..size {
+                for delta in -20i64..=20 {
+                    let targets = [0, 3, size - 1];
+                    let dial = Dial::new(size, start, &targets);
+                    let step = delta.signum();
+                    let expected = (1..=delta.abs())
+                        .filter(|k| dial.targets.contains(&(start + step * k).rem_euclid(size)))
+                        .count() as u64;
+                    assert_eq!(
+                        dial.crossings(delta),
+                        expected,
+                        "size {} start {} delta {}",
+                        size,
+                        start,
+                        delta
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_landings_multiple_targets() {
+        let mut dial = Dial::new(10, 0, &[3, 7, 13]);
+        assert_eq!(dial.rotate(3, CountMode::Landings), 1);
+        assert_eq!(dial.rotate(-6, CountMode::Landings), 1); // 3 -> 7 going left
+        assert_eq!(dial.rotate(1, CountMode::Landings), 0);
+        assert_eq!(dial.position, 8);
+    }
+}
@@ -1,6 +1,8 @@
-use std::collections::HashSet;
+use std::fmt::Write;
 
-use crate::read_input_raw;
+use crate::bigint::BigUint;
+use crate::parse::{ParseError, parse_lines};
+use crate::{read_input_raw, write_debug};
 
 pub fn run() {
     let input = read_input_raw("day_02");
@@ -8,34 +10,88 @@ pub fn run() {
     // Part 1: Sum all invalid IDs in the given ranges
     // Invalid IDs are numbers made of a digit sequence repeated exactly twice (e.g., 55, 6464, 123123)
 
-    let ranges: Vec<(u64, u64)> = input
-        .split(',')
-        .filter(|s| !s.trim().is_empty())
-        .map(|range| {
-            let parts: Vec<&str> = range.trim().split('-').collect();
-            let start: u64 = parts[0].parse().unwrap();
-            let end: u64 = parts[1].parse().unwrap();
-            (start, end)
-        })
-        .collect();
-
-    let mut sum: u64 = 0;
-    for (start, end) in &ranges {
-        sum += sum_invalid_in_range(*start, *end);
-    }
+    // Bounds are read as u128 and sums kept as big integers, so IDs of 30+ digits work
+    let ranges = parse_ranges(&input).unwrap();
 
-    let res01 = sum;
+    let res01: BigUint = ranges
+        .iter()
+        .map(|&(start, end)| sum_invalid_in_range(start, end))
+        .sum();
 
     // Part 2: Invalid IDs are now patterns repeated at least twice (e.g., 111, 1212, 123123123)
-    let mut sum2: u64 = 0;
-    for (start, end) in &ranges {
-        sum2 += sum_invalid_in_range_part2(*start, *end);
-    }
-
-    let res02 = sum2;
+    let res02: BigUint = ranges
+        .iter()
+        .map(|&(start, end)| sum_invalid_in_range_part2(start, end))
+        .sum();
 
     println!("Part 1 answer: {}", res01);
     println!("Part 2 answer: {}", res02);
+
+    // Set AOC_DEBUG_DIR to dump the invalid IDs with the block that forms them
+    for (part, rule) in [
+        (1, RepetitionRule::Exactly(2)),
+        (2, RepetitionRule::AtLeast(2)),
+    ] {
+        let pattern = IdPattern::new(rule, 10);
+        write_debug(&format!("day_02_part{}_ids.csv", part), || {
+            ids_to_csv(&pattern, &ranges, DEBUG_ID_LIMIT)
+        });
+    }
+}
+
+/// Parse the comma-separated `start-end` ranges, skipping empty entries.
+fn parse_ranges(input: &str) -> Result<Vec<(u128, u128)>, ParseError> {
+    let lines: Vec<&str> = input.lines().collect();
+    let ranges = parse_lines(&lines, |c| {
+        let mut ranges = Vec::new();
+        loop {
+            // Empty entries, like a trailing comma, are skipped
+            while c.skip_ws().tag(",").is_ok() {}
+            if c.is_empty() {
+                return Ok(ranges);
+            }
+            let start = c.int()?;
+            c.skip_ws().tag("-")?;
+            ranges.push((start, c.skip_ws().int()?));
+            if c.skip_ws().tag(",").is_err() {
+                return Ok(ranges);
+            }
+        }
+    })?;
+    Ok(ranges.concat())
+}
+
+/// Most invalid IDs written to a debug CSV; a range like 1-10^18 holds billions.
+const DEBUG_ID_LIMIT: usize = 100_000;
+
+/// The first `limit` invalid IDs across the ranges as CSV, one row per ID.
+fn ids_to_csv(pattern: &IdPattern, ranges: &[(u128, u128)], limit: usize) -> String {
+    let mut csv = String::from("start,end,id,block,repeats\n");
+    let ids = ranges
+        .iter()
+        .flat_map(|&(start, end)| pattern.ids(start, end).map(move |id| (start, end, id)));
+    for (start, end, invalid) in ids.take(limit) {
+        writeln!(
+            csv,
+            "{},{},{},{},{}",
+            start, end, invalid.id, invalid.block, invalid.repeats
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// Sum of `base * multiplier` for every base in [lo, hi], without overflow.
+fn sum_of_multiples(lo: u128, hi: u128, multiplier: u128) -> BigUint {
+    // Sum of consecutive integers from a to b = (b - a + 1) * (a + b) / 2.
+    // One of the two factors is always even, so halve that one first.
+    let count = hi - lo + 1;
+    let (count, ends) = if count.is_multiple_of(2) {
+        (count / 2, BigUint::from(lo) + BigUint::from(hi))
+    } else {
+        (count, BigUint::from(lo / 2 + hi / 2 + (lo % 2 + hi % 2) / 2))
+    };
+    BigUint::from(count) * ends * BigUint::from(multiplier)
 }
 
 /// Sum all invalid numbers in range [start, end].
@@ -45,55 +101,8 @@ pub fn run() {
 /// - 1010, 1111, ..., 9999 (two digits repeated)
 /// - 100100, 101101, ..., 999999 (three digits repeated)
 /// - etc.
-///
-/// For a k-digit base number n, the invalid number is n * (10^k + 1).
-/// For example: base=123, k=3, multiplier=1001, invalid=123123
-fn sum_invalid_in_range(start: u64, end: u64) -> u64 {
-    let mut sum = 0;
-
-    // Generate invalid numbers by digit length of the "base" number
-    // k=1: base 1-9, multiplier=11, invalid numbers 11-99
-    // k=2: base 10-99, multiplier=101, invalid numbers 1010-9999
-    // k=3: base 100-999, multiplier=1001, invalid numbers 100100-999999
-    // etc.
-    for k in 1..=10 {
-        // Use checked arithmetic to avoid overflow
-        let Some(ten_pow_k) = 10u64.checked_pow(k) else {
-            break;
-        };
-        let multiplier = ten_pow_k + 1;
-        let base_start = if k == 1 { 1 } else { 10u64.pow(k - 1) };
-        let base_end = ten_pow_k - 1;
-
-        // Range of invalid numbers for this k
-        let invalid_min = base_start.saturating_mul(multiplier);
-        let Some(invalid_max) = base_end.checked_mul(multiplier) else {
-            break; // Numbers too large, stop here
-        };
-
-        // Skip if no overlap with our target range
-        if invalid_max < start || invalid_min > end {
-            continue;
-        }
-
-        // Find the range of base values that produce invalid numbers within [start, end]
-        // For invalid number to be >= start: base * multiplier >= start => base >= start / multiplier (rounded up)
-        // For invalid number to be <= end: base * multiplier <= end => base <= end / multiplier
-        let actual_base_start = ((start + multiplier - 1) / multiplier).max(base_start);
-        let actual_base_end = (end / multiplier).min(base_end);
-
-        if actual_base_start > actual_base_end {
-            continue;
-        }
-
-        // Sum of invalid numbers = multiplier * sum of bases from actual_base_start to actual_base_end
-        // Sum of consecutive integers from a to b = (b - a + 1) * (a + b) / 2
-        let count = actual_base_end - actual_base_start + 1;
-        let base_sum = count * (actual_base_start + actual_base_end) / 2;
-        sum += multiplier * base_sum;
-    }
-
-    sum
+fn sum_invalid_in_range(start: u128, end: u128) -> BigUint {
+    IdPattern::new(RepetitionRule::Exactly(2), 10).sum(start, end)
 }
 
 /// Sum all invalid numbers in range [start, end] for Part 2.
@@ -102,68 +111,316 @@ fn sum_invalid_in_range(start: u64, end: u64) -> u64 {
 /// - 11, 111, 1111, ... (1 repeated 2+, 3+, 4+ times)
 /// - 1212, 121212, ... (12 repeated 2+, 3+ times)
 /// - etc.
+fn sum_invalid_in_range_part2(start: u128, end: u128) -> BigUint {
+    IdPattern::new(RepetitionRule::AtLeast(2), 10).sum(start, end)
+}
+
+/// Which repeat counts make an ID invalid. A count of 1 never does, so
+/// `AtMost(r)` means 2..=r.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepetitionRule {
+    Exactly(u32),
+    AtLeast(u32),
+    AtMost(u32),
+    OneOf(Vec<u32>),
+}
+
+impl RepetitionRule {
+    pub fn allows(&self, repeats: u32) -> bool {
+        repeats >= 2
+            && match self {
+                RepetitionRule::Exactly(r) => repeats == *r,
+                RepetitionRule::AtLeast(r) => repeats >= *r,
+                RepetitionRule::AtMost(r) => repeats <= *r,
+                RepetitionRule::OneOf(rs) => rs.contains(&repeats),
+            }
+    }
+}
+
+/// Numbers whose digits in `radix` are a block repeated a number of times
+/// allowed by `rule`, e.g. `Exactly(3)` in base 16 matches 0xABABAB.
+#[derive(Debug, Clone)]
+pub struct IdPattern {
+    pub rule: RepetitionRule,
+    pub radix: u32,
+}
+
+/// A matching number, with the block that repeats to form it.
 ///
-/// For a k-digit base number n repeated r times, the invalid number is:
-/// n * (10^(k*r) - 1) / (10^k - 1)
-///
-/// We use a HashSet to avoid counting duplicates (e.g., 1111 = "1"×4 = "11"×2)
-fn sum_invalid_in_range_part2(start: u64, end: u64) -> u64 {
-    let mut invalid_set: HashSet<u64> = HashSet::new();
-
-    // For each pattern length k and repetition count r (r >= 2)
-    for k in 1u32..=10 {
-        for r in 2u32..=20 {
-            let total_digits = k * r;
-            if total_digits > 19 {
-                break; // u64 max is ~19 digits
+/// IDs such as 1111 have several decompositions; this is the one with the
+/// shortest block the rule allows ("1" x 4 for at-least-twice, "11" x 2 for
+/// exactly-twice).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidId {
+    pub id: u128,
+    pub block: u128,
+    pub repeats: u32,
+}
+
+/// Iterator over the matching numbers in a range; see [`IdPattern::ids`].
+pub struct InvalidIds<'a> {
+    pattern: &'a IdPattern,
+    start: u128,
+    end: u128,
+    // Digit length currently being walked
+    len: u32,
+    streams: Vec<BlockStream>,
+}
+
+/// The blocks of one period for the current length, walked in order.
+struct BlockStream {
+    period: u32,
+    block: u128,
+    hi: u128,
+    multiplier: u128,
+}
+
+impl Iterator for InvalidIds<'_> {
+    type Item = InvalidId;
+
+    fn next(&mut self) -> Option<InvalidId> {
+        loop {
+            // Merge the streams of every period, preferring the shortest on ties
+            let best = self
+                .streams
+                .iter()
+                .filter(|s| s.block <= s.hi)
+                .map(|s| (s.block * s.multiplier, s.period))
+                .min();
+
+            if let Some((id, period)) = best {
+                let mut found = None;
+                for s in self.streams.iter_mut() {
+                    if s.block <= s.hi && s.block * s.multiplier == id {
+                        if s.period == period {
+                            found = Some(InvalidId {
+                                id,
+                                block: s.block,
+                                repeats: self.len / period,
+                            });
+                        }
+                        s.block += 1;
+                    }
+                }
+                return found;
             }
 
-            // multiplier = (10^(k*r) - 1) / (10^k - 1)
-            // e.g., k=1, r=3: (1000-1)/(10-1) = 999/9 = 111
-            // e.g., k=2, r=3: (1000000-1)/(100-1) = 999999/99 = 10101
-            let Some(ten_pow_kr) = 10u64.checked_pow(total_digits) else {
-                break;
-            };
-            let ten_pow_k = 10u64.pow(k);
-            let multiplier = (ten_pow_kr - 1) / (ten_pow_k - 1);
-
-            // Base range for k-digit numbers (no leading zeros)
-            let base_start = if k == 1 { 1 } else { 10u64.pow(k - 1) };
-            let base_end = ten_pow_k - 1;
-
-            // Range of invalid numbers for this (k, r)
-            let invalid_min = base_start.saturating_mul(multiplier);
-            let Some(invalid_max) = base_end.checked_mul(multiplier) else {
-                break;
-            };
-
-            // Skip if no overlap with target range
-            if invalid_max < start || invalid_min > end {
-                continue;
+            // This length is exhausted; every longer ID is larger
+            self.len += 1;
+            let min = (self.pattern.radix as u128).checked_pow(self.len - 1)?;
+            if min > self.end {
+                return None;
             }
+            let (pattern, start, end, len) = (self.pattern, self.start, self.end, self.len);
+            self.streams = pattern
+                .periods(len)
+                .into_iter()
+                .filter_map(|period| {
+                    let (lo, hi, multiplier) = pattern.blocks(start, end, period, len)?;
+                    Some(BlockStream {
+                        period,
+                        block: lo,
+                        hi,
+                        multiplier,
+                    })
+                })
+                .collect();
+        }
+    }
+}
+
+/// One closed-form piece of a count or sum: every block in [lo, hi] times
+/// `multiplier`, weighted by `coefficient`.
+struct Term {
+    coefficient: i64,
+    lo: u128,
+    hi: u128,
+    multiplier: u128,
+}
+
+impl IdPattern {
+    pub fn new(rule: RepetitionRule, radix: u32) -> Self {
+        assert!(radix >= 2, "Radix must be at least 2");
+        IdPattern { rule, radix }
+    }
+
+    /// Whether a single number matches, by checking its digits directly.
+    pub fn matches(&self, n: u128) -> bool {
+        let digits = self.digits(n);
+        let len = digits.len() as u32;
+        (2..=len).any(|r| {
+            let block = (len / r) as usize;
+            len.is_multiple_of(r)
+                && self.rule.allows(r)
+                && digits.chunks(block).all(|c| c == &digits[..block])
+        })
+    }
+
+    /// Every matching number in [start, end], ascending.
+    pub fn list(&self, start: u128, end: u128) -> Vec<u128> {
+        self.ids(start, end).map(|invalid| invalid.id).collect()
+    }
 
-            // Find bases that produce invalid numbers in [start, end]
-            let actual_base_start = ((start + multiplier - 1) / multiplier).max(base_start);
-            let actual_base_end = (end / multiplier).min(base_end);
+    /// Lazily walk the matching numbers in [start, end] in ascending order,
+    /// each with the block and repeat count that produce it.
+    pub fn ids(&self, start: u128, end: u128) -> InvalidIds<'_> {
+        InvalidIds {
+            pattern: self,
+            start,
+            end,
+            len: 0,
+            streams: Vec::new(),
+        }
+    }
+
+    /// How many numbers in [start, end] match.
+    pub fn count(&self, start: u128, end: u128) -> u128 {
+        let (mut added, mut removed) = (0u128, 0u128);
+        for t in self.terms(start, end) {
+            let count = (t.hi - t.lo + 1) * t.coefficient.unsigned_abs() as u128;
+            if t.coefficient > 0 {
+                added += count;
+            } else {
+                removed += count;
+            }
+        }
+        added - removed
+    }
 
-            if actual_base_start > actual_base_end {
-                continue;
+    /// Sum of the matching numbers in [start, end].
+    pub fn sum(&self, start: u128, end: u128) -> BigUint {
+        let mut added = BigUint::zero();
+        let mut removed = BigUint::zero();
+        for t in self.terms(start, end) {
+            let sum = sum_of_multiples(t.lo, t.hi, t.multiplier)
+                * BigUint::from(t.coefficient.unsigned_abs());
+            if t.coefficient > 0 {
+                added += sum;
+            } else {
+                removed += sum;
             }
+        }
+        added - removed
+    }
+
+    fn digits(&self, mut n: u128) -> Vec<u128> {
+        let mut digits = Vec::new();
+        while n > 0 {
+            digits.push(n % self.radix as u128);
+            n /= self.radix as u128;
+        }
+        digits.reverse();
+        digits
+    }
+
+    /// Digit lengths whose smallest number is at most `end`.
+    fn lengths(&self, end: u128) -> impl Iterator<Item = u32> {
+        let radix = self.radix as u128;
+        (1u32..)
+            .map_while(move |len| radix.checked_pow(len - 1).map(|min| (len, min)))
+            .take_while(move |&(_, min)| min <= end)
+            .map(|(len, _)| len)
+    }
+
+    /// Block lengths that make a `len`-digit ID invalid.
+    fn periods(&self, len: u32) -> Vec<u32> {
+        (2..=len)
+            .filter(|&r| len.is_multiple_of(r) && self.rule.allows(r))
+            .map(|r| len / r)
+            .collect()
+    }
+
+    /// The `period`-digit blocks (no leading zeros) whose repetition out to
+    /// `len` digits lands in [start, end], with the repeating multiplier.
+    ///
+    /// For a k-digit block n repeated r times, the number is
+    /// n * (radix^(k*r) - 1) / (radix^k - 1), e.g. 123 * 1001001 = 123123123.
+    fn blocks(&self, start: u128, end: u128, period: u32, len: u32) -> Option<(u128, u128, u128)> {
+        let radix = self.radix as u128;
+        let shift = radix.pow(period);
+        // 1 + radix^k + radix^2k + ...; if it overflows, so does every ID
+        let mut multiplier = 0u128;
+        for _ in 0..len / period {
+            multiplier = multiplier.checked_mul(shift)?.checked_add(1)?;
+        }
+
+        let lo = start.div_ceil(multiplier).max(radix.pow(period - 1));
+        let hi = (end / multiplier).min(shift - 1);
+        (lo <= hi).then_some((lo, hi, multiplier))
+    }
 
-            // Add all invalid numbers from this (k, r) to the set
-            for base in actual_base_start..=actual_base_end {
-                invalid_set.insert(base * multiplier);
+    /// Inclusion–exclusion over the periods of each length.
+    ///
+    /// IDs with periods d and e are exactly those with period gcd(d, e), so the
+    /// union over the allowed periods D collapses onto the divisors g of the
+    /// length with weight Σ_{g | h | len} μ(h/g) · [some d in D is a multiple of h].
+    /// For "at least twice" that is -μ(len/g), and each piece is closed-form, so
+    /// the cost doesn't depend on the size of the range.
+    fn terms(&self, start: u128, end: u128) -> Vec<Term> {
+        let mut terms = Vec::new();
+        for len in self.lengths(end) {
+            let periods = self.periods(len);
+            let divisors: Vec<u32> = (1..len).filter(|&g| len.is_multiple_of(g)).collect();
+            for &g in &divisors {
+                let coefficient: i64 = divisors
+                    .iter()
+                    .filter(|&&h| h.is_multiple_of(g))
+                    .filter(|&&h| periods.iter().any(|d| d.is_multiple_of(h)))
+                    .map(|&h| mobius(h / g) as i64)
+                    .sum();
+                if coefficient == 0 {
+                    continue;
+                }
+                if let Some((lo, hi, multiplier)) = self.blocks(start, end, g, len) {
+                    terms.push(Term {
+                        coefficient,
+                        lo,
+                        hi,
+                        multiplier,
+                    });
+                }
             }
         }
+        terms
     }
+}
 
-    invalid_set.iter().sum()
+/// The Möbius function: 0 if n has a squared prime factor, otherwise (-1)^(number of primes).
+fn mobius(mut n: u32) -> i32 {
+    let mut result = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            result = -result;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        result = -result;
+    }
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_ranges() {
+        assert_eq!(
+            parse_ranges("11-22, 95-115,\n998-1012,"),
+            Ok(vec![(11, 22), (95, 115), (998, 1012)])
+        );
+        let err = parse_ranges("11-22,95+115").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 9));
+        let err = parse_ranges("11-22\n95-1x5").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+    }
+
     #[test]
     fn test_is_invalid_pattern() {
         // Test by checking sum_invalid_in_range for single numbers
@@ -208,8 +465,170 @@ mod tests {
         assert_eq!(total, 1227775554);
     }
 
+    #[test]
+    fn test_beyond_u64() {
+        // "123456789012345" x 2 is a 30-digit ID, far past u64::MAX
+        let id: u128 = 123456789012345123456789012345;
+        assert_eq!(sum_invalid_in_range(id - 5, id + 5), id);
+        assert_eq!(sum_invalid_in_range_part2(id - 5, id + 5), id);
+
+        // "12" x 15 only counts for part 2
+        let id: u128 = 121212121212121212121212121212;
+        assert_eq!(sum_invalid_in_range(id, id), 0);
+        assert_eq!(sum_invalid_in_range_part2(id, id), id);
+
+        // Every doubled ID up to 38 digits: the sum itself overflows u128
+        let total = sum_invalid_in_range(1, 10u128.pow(38) - 1);
+        assert_eq!(
+            total.to_string(),
+            "495495495495495495500040950040950040945040950040950040950"
+        );
+    }
+
     // Part 2 tests
 
+    /// Brute-force Part 2 by checking every number's digits.
+    fn brute_force_part2(start: u128, end: u128) -> u128 {
+        (start..=end)
+            .filter(|n| {
+                let digits = n.to_string();
+                let len = digits.len();
+                (1..len).any(|k| len % k == 0 && digits == digits[..k].repeat(len / k))
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_part2_matches_brute_force() {
+        for (start, end) in [(1, 100_000), (999_000, 1_001_000), (11_111_100, 11_111_200)] {
+            assert_eq!(
+                sum_invalid_in_range_part2(start, end),
+                brute_force_part2(start, end)
+            );
+        }
+    }
+
+    /// Sum of the invalid IDs with exactly `len` digits, grouped by smallest
+    /// period: the IDs made of a p-digit block are exactly those whose smallest
+    /// period divides p, so peel those off in increasing order of p.
+    fn sum_invalid_of_length(len: u32) -> u128 {
+        let mut smallest = vec![0u128; len as usize];
+        for p in (1..len).filter(|&p| len.is_multiple_of(p)) {
+            let (lo, hi) = (10u128.pow(p - 1), 10u128.pow(p) - 1);
+            let blocks = (lo + hi) * (hi - lo + 1) / 2;
+            let repeat = (10u128.pow(len) - 1) / hi;
+            let shorter: u128 = (1..p)
+                .filter(|&q| p.is_multiple_of(q))
+                .map(|q| smallest[q as usize])
+                .sum();
+            smallest[p as usize] = blocks * repeat - shorter;
+        }
+        smallest.iter().sum()
+    }
+
+    #[test]
+    fn test_part2_huge_range() {
+        assert_eq!(sum_invalid_of_length(4), brute_force_part2(1000, 9999));
+
+        // Every invalid ID below 10^12
+        let expected: u128 = (1..=12).map(sum_invalid_of_length).sum();
+        assert_eq!(expected, 500397481094131395);
+        assert_eq!(sum_invalid_in_range_part2(1, 10u128.pow(12) - 1), expected);
+
+        // 1..10^18 holds billions of invalid IDs; only feasible in closed form
+        let expected: u128 = (1..=18).map(sum_invalid_of_length).sum();
+        assert_eq!(sum_invalid_in_range_part2(1, 10u128.pow(18)), expected);
+    }
+
+    #[test]
+    fn test_repetition_rules_match_brute_force() {
+        let rules = [
+            RepetitionRule::Exactly(2),
+            RepetitionRule::Exactly(3),
+            RepetitionRule::AtLeast(3),
+            RepetitionRule::AtMost(3),
+            RepetitionRule::OneOf(vec![2, 5]),
+        ];
+        for rule in rules {
+            for radix in [2, 10, 16] {
+                let pattern = IdPattern::new(rule.clone(), radix);
+                let (start, end) = (1, 50_000);
+                let expected: Vec<u128> = (start..=end).filter(|&n| pattern.matches(n)).collect();
+
+                let context = format!("{:?} base {}", rule, radix);
+                assert_eq!(pattern.list(start, end), expected, "{}", context);
+                assert_eq!(pattern.count(start, end), expected.len() as u128);
+                assert_eq!(pattern.sum(start, end), expected.iter().sum::<u128>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_ids_iterator() {
+        let part2 = IdPattern::new(RepetitionRule::AtLeast(2), 10);
+        let ids: Vec<InvalidId> = part2.ids(95, 1112).collect();
+        let as_tuples: Vec<_> = ids.iter().map(|i| (i.id, i.block, i.repeats)).collect();
+        assert_eq!(
+            as_tuples,
+            vec![
+                (99, 9, 2),
+                (111, 1, 3),
+                (222, 2, 3),
+                (333, 3, 3),
+                (444, 4, 3),
+                (555, 5, 3),
+                (666, 6, 3),
+                (777, 7, 3),
+                (888, 8, 3),
+                (999, 9, 3),
+                (1010, 10, 2),
+                (1111, 1, 4),
+            ]
+        );
+
+        // Exactly twice reads 1111 as "11" x 2
+        let part1 = IdPattern::new(RepetitionRule::Exactly(2), 10);
+        let first = part1.ids(1111, 1111).next().unwrap();
+        assert_eq!((first.block, first.repeats), (11, 2));
+
+        // Lazy, so the front of an astronomically large range is cheap
+        let front: Vec<u128> = part2.ids(1, 10u128.pow(30)).take(3).map(|i| i.id).collect();
+        assert_eq!(front, vec![11, 22, 33]);
+    }
+
+    #[test]
+    fn test_ids_csv() {
+        let pattern = IdPattern::new(RepetitionRule::Exactly(2), 10);
+        assert_eq!(
+            ids_to_csv(&pattern, &[(11, 22), (95, 115)], 10),
+            "start,end,id,block,repeats\n11,22,11,1,2\n11,22,22,2,2\n95,115,99,9,2\n"
+        );
+
+        // Huge ranges stop at the limit instead of listing billions of rows
+        let csv = ids_to_csv(&pattern, &[(1, 10u128.pow(18)), (95, 115)], 3);
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.ends_with("1,1000000000000000000,33,3,2\n"));
+    }
+
+    #[test]
+    fn test_hex_repeated_three_times() {
+        let pattern = IdPattern::new(RepetitionRule::Exactly(3), 16);
+        assert!(pattern.matches(0xABABAB));
+        assert!(pattern.matches(0x777));
+        assert!(!pattern.matches(0xABAB));
+        assert_eq!(pattern.list(0x100, 0x200), vec![0x111]);
+        // 15 single-digit blocks and 240 two-digit blocks
+        assert_eq!(pattern.count(1, 0xFFFFFF), 15 + 240);
+    }
+
+    #[test]
+    fn test_mobius() {
+        let expected = [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0];
+        for (n, &mu) in (1..=12).zip(&expected) {
+            assert_eq!(mobius(n), mu, "mu({})", n);
+        }
+    }
+
     #[test]
     fn test_part2_is_invalid_pattern() {
         // Test Part 2 patterns (at least 2 repetitions)
@@ -1,75 +1,133 @@
+use std::fmt;
+
+use crate::bigint::BigUint;
+use crate::parse::{ParseError, parse_lines};
 use crate::read_input;
 
 pub fn run() {
     let input = read_input("day_03");
 
     // Part 1: Find the maximum two-digit joltage from each bank and sum them
-    let res01: u32 = input.iter().map(|bank| max_joltage(bank)).sum();
+    let res01: BigUint = parse_banks(&input, 2)
+        .unwrap()
+        .iter()
+        .map(|bank| select_batteries(bank, 2, Order::Largest).value())
+        .sum();
 
     // Part 2: Find the maximum 12-digit joltage from each bank and sum them
-    let res02: u64 = input.iter().map(|bank| max_joltage_k(bank, 12)).sum();
+    let res02: BigUint = parse_banks(&input, 12)
+        .unwrap()
+        .iter()
+        .map(|bank| select_batteries(bank, 12, Order::Largest).value())
+        .sum();
 
     println!("Part 1 answer: {}", res01);
     println!("Part 2 answer: {}", res02);
 }
 
-/// Find the maximum joltage (two-digit number) that can be formed by
-/// selecting exactly two batteries from the bank, maintaining their order.
-fn max_joltage(bank: &str) -> u32 {
-    let digits: Vec<u32> = bank.chars().map(|c| c.to_digit(10).unwrap()).collect();
-    let n = digits.len();
+/// Parse one bank of digits per line, checking that each can supply k batteries.
+pub fn parse_banks<S: AsRef<str>>(lines: &[S], k: usize) -> Result<Vec<Vec<u32>>, ParseError> {
+    parse_lines(lines, |c| {
+        let digits = c.take_while(|ch| ch.is_ascii_digit());
+        if let Some(ch) = c.peek() {
+            return Err(c.error(format!("expected a digit, found '{}'", ch)));
+        }
+        if digits.len() < k {
+            return Err(c.error(format!("bank too short for k = {}", k)));
+        }
+        Ok(digits.bytes().map(|b| (b - b'0') as u32).collect())
+    })
+}
 
-    let mut max_jolt = 0;
+/// Whether to build the largest or the smallest joltage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Largest,
+    Smallest,
+}
+
+/// The batteries switched on, in bank order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub digits: Vec<u32>,
+    pub positions: Vec<usize>,
+}
 
-    // For each possible first battery position
-    for i in 0..n - 1 {
-        let first = digits[i];
-        // Find the maximum digit after position i
-        let second = *digits[i + 1..].iter().max().unwrap();
-        let joltage = first * 10 + second;
-        max_jolt = max_jolt.max(joltage);
+impl Selection {
+    /// The joltage read as a number. It can run to any length, so k = 50 or
+    /// a bank of thousands of digits doesn't overflow.
+    pub fn value(&self) -> BigUint {
+        if self.digits.is_empty() {
+            return BigUint::zero();
+        }
+        self.to_string().parse().unwrap()
     }
+}
 
-    max_jolt
+impl fmt::Display for Selection {
+    /// The chosen digits, leading zeros included.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.digits {
+            write!(f, "{}", d)?;
+        }
+        Ok(())
+    }
 }
 
-/// Find the maximum joltage (k-digit number) that can be formed by
-/// selecting exactly k batteries from the bank, maintaining their order.
+/// Select exactly k batteries from the bank, keeping their order, so the
+/// digits read as the largest (or smallest) possible number.
 ///
-/// Uses a greedy approach: at each step, pick the largest digit from the
-/// valid range (ensuring enough digits remain for the remaining positions).
-fn max_joltage_k(bank: &str, k: usize) -> u64 {
-    use std::cmp::Reverse;
-
-    let digits: Vec<u32> = bank.chars().map(|c| c.to_digit(10).unwrap()).collect();
+/// Uses a monotonic stack: each new digit pops the digits before it that it
+/// beats, as long as enough digits remain to still fill k places. Every digit
+/// is pushed and popped at most once, so this is O(n) for any k. Ties keep the
+/// earliest battery.
+///
+/// Panics if the bank has fewer than k batteries; [`parse_banks`] rules that out.
+pub fn select_batteries(digits: &[u32], k: usize, order: Order) -> Selection {
     let n = digits.len();
+    assert!(k <= n, "Bank of {} batteries is too short for k = {}", n, k);
+    let beats = |new: u32, old: u32| match order {
+        Order::Largest => new > old,
+        Order::Smallest => new < old,
+    };
 
-    let mut result: u64 = 0;
-    let mut start = 0;
-
-    for remaining in (1..=k).rev() {
-        // We can pick from start to (n - remaining) inclusive
-        // This ensures we have enough positions left for remaining-1 more picks
-        let end = n - remaining;
-
-        // Find the FIRST position with the maximum digit in [start, end]
-        // Using min_by_key with Reverse to get max digit, then smallest index on ties
-        let (best_pos, best_digit) = (start..=end)
-            .map(|i| (i, digits[i]))
-            .min_by_key(|&(i, d)| (Reverse(d), i))
-            .unwrap();
-
-        result = result * 10 + best_digit as u64;
-        start = best_pos + 1;
+    // Positions of the batteries chosen so far
+    let mut stack: Vec<usize> = Vec::with_capacity(k);
+    for (i, &d) in digits.iter().enumerate() {
+        while let Some(&top) = stack.last() {
+            // Popping is only allowed if the rest of the bank can refill the stack
+            if !beats(d, digits[top]) || stack.len() - 1 + (n - i) < k {
+                break;
+            }
+            stack.pop();
+        }
+        if stack.len() < k {
+            stack.push(i);
+        }
     }
 
-    result
+    Selection {
+        digits: stack.iter().map(|&i| digits[i]).collect(),
+        positions: stack,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn select(bank: &str, k: usize, order: Order) -> Selection {
+        select_batteries(&parse_banks(&[bank], k).unwrap()[0], k, order)
+    }
+
+    fn max_joltage(bank: &str) -> BigUint {
+        select(bank, 2, Order::Largest).value()
+    }
+
+    fn max_joltage_k(bank: &str, k: usize) -> BigUint {
+        select(bank, k, Order::Largest).value()
+    }
+
     #[test]
     fn test_max_joltage() {
         assert_eq!(max_joltage("987654321111111"), 98);
@@ -78,15 +136,66 @@ mod tests {
         assert_eq!(max_joltage("818181911112111"), 92);
     }
 
+    #[test]
+    fn test_selection_positions() {
+        let selection = select("818181911112111", 2, Order::Largest);
+        assert_eq!(selection.digits, vec![9, 2]);
+        assert_eq!(selection.positions, vec![6, 11]);
+
+        // Ties keep the earliest battery
+        let selection = select("1919", 2, Order::Largest);
+        assert_eq!(selection.positions, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_smallest_and_full_length() {
+        let selection = select("818181911112111", 4, Order::Smallest);
+        assert_eq!(selection.value(), 1111);
+        assert_eq!(selection.positions, vec![1, 3, 5, 7]);
+        assert_eq!(select("3142", 2, Order::Smallest).value(), 12);
+
+        // k equal to the bank length takes everything
+        assert_eq!(max_joltage_k("3142", 4), 3142);
+        assert_eq!(select("3142", 0, Order::Largest).value(), 0);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        // Every k-subset of a handful of banks, compared as digit strings
+        for bank in ["3142", "9090909", "1122334455", "5432112345"] {
+            let digits = bank.as_bytes();
+            for k in 0..=digits.len() {
+                let picks = (0u32..1 << digits.len()).filter(|m| m.count_ones() as usize == k);
+                let options: Vec<Vec<u8>> = picks
+                    .map(|m| {
+                        (0..digits.len())
+                            .filter(|i| m >> i & 1 == 1)
+                            .map(|i| digits[i])
+                            .collect()
+                    })
+                    .collect();
+                let best = |order| {
+                    let s = select(bank, k, order);
+                    s.digits
+                        .iter()
+                        .map(|&d| b'0' + d as u8)
+                        .collect::<Vec<u8>>()
+                };
+                assert_eq!(&best(Order::Largest), options.iter().max().unwrap());
+                assert_eq!(&best(Order::Smallest), options.iter().min().unwrap());
+            }
+        }
+    }
+
     #[test]
     fn test_example_sum() {
-        let banks = vec![
+        let banks = [
             "987654321111111",
             "811111111111119",
             "234234234234278",
             "818181911112111",
         ];
-        let total: u32 = banks.iter().map(|b| max_joltage(b)).sum();
+        let total: BigUint = banks.iter().map(|b| max_joltage(b)).sum();
         assert_eq!(total, 357);
     }
 
@@ -102,14 +211,51 @@ mod tests {
 
     #[test]
     fn test_part2_example_sum() {
-        let banks = vec![
+        let banks = [
             "987654321111111",
             "811111111111119",
             "234234234234278",
             "818181911112111",
         ];
-        let total: u64 = banks.iter().map(|b| max_joltage_k(b, 12)).sum();
+        let total: BigUint = banks.iter().map(|b| max_joltage_k(b, 12)).sum();
         assert_eq!(total, 3121910778619);
     }
-}
 
+    #[test]
+    fn test_long_joltages() {
+        // 50 digits is far past u64, and the sum of two is past u128 too
+        let bank = format!("1{}", "0".repeat(59));
+        let total: BigUint = [bank.as_str(), bank.as_str()]
+            .iter()
+            .map(|b| max_joltage_k(b, 50))
+            .sum();
+        assert_eq!(total.to_string(), format!("2{}", "0".repeat(49)));
+
+        // Thousands of digits: the 3000 nines beat everything before them
+        let bank = format!("{}{}", "12345678".repeat(500), "9".repeat(3000));
+        let selection = select(&bank, 3000, Order::Largest);
+        assert_eq!(selection.to_string(), "9".repeat(3000));
+        assert_eq!(selection.positions[0], 4000);
+
+        // Leading zeros are kept in the digits but not the value
+        let selection = select("90807", 3, Order::Smallest);
+        assert_eq!(selection.to_string(), "007");
+        assert_eq!(selection.value(), 7);
+    }
+
+    #[test]
+    fn test_invalid_banks() {
+        let err = parse_banks(&["12345", "", "12a45"], 2).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.message, "expected a digit, found 'a'");
+
+        let err = parse_banks(&["12345", "7"], 2).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.message, "bank too short for k = 2");
+
+        // Fine for part 1, too short for part 2
+        let banks = ["987654321"];
+        assert!(parse_banks(&banks, 2).is_ok());
+        assert!(parse_banks(&banks, 12).is_err());
+    }
+}
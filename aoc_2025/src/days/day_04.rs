@@ -1,108 +1,462 @@
-use crate::read_input;
+use std::collections::VecDeque;
+
+use crate::{read_input, write_debug};
 
 pub fn run() {
     let input = read_input("day_04");
+    let rule = Rule::default();
 
     // Part 1: Count rolls that can be accessed (fewer than 4 adjacent rolls)
     let grid: Vec<Vec<char>> = input.iter().map(|line| line.chars().collect()).collect();
-    let res01 = count_accessible_rolls(&grid);
+    let res01 = count_accessible_rolls(&grid, &rule);
 
     // Part 2: Remove rolls iteratively until no more can be removed
-    let res02 = count_total_removable(&grid);
+    let res02 = count_total_removable(&grid, &rule);
 
     println!("Part 1 answer: {}", res01);
     println!("Part 2 answer: {}", res02);
+
+    // Set AOC_DEBUG_DIR to dump the grid labelled by removal round
+    write_debug("day_04_layers.txt", || {
+        peel(&BitGrid::from_grid(&grid), &rule).render(&grid)
+    });
+}
+
+/// Which cells around a roll count as its neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Cells within the radius in Manhattan distance (orthogonal at radius 1).
+    VonNeumann,
+    /// Cells within the radius in Chebyshev distance (all 8 around at radius 1).
+    Moore,
+    /// Explicit (row, column) offsets; the radius is ignored.
+    Custom(Vec<(isize, isize)>),
+}
+
+/// When a roll is accessible: it has fewer than `threshold` rolls in its
+/// neighbourhood. With `wrap` set the grid is a torus, so edges wrap around.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub neighbourhood: Neighbourhood,
+    pub radius: usize,
+    pub threshold: usize,
+    pub wrap: bool,
+}
+
+impl Default for Rule {
+    /// The puzzle's rule: fewer than 4 rolls in the 8 cells around.
+    fn default() -> Self {
+        Rule {
+            neighbourhood: Neighbourhood::Moore,
+            radius: 1,
+            threshold: 4,
+            wrap: false,
+        }
+    }
+}
+
+impl Rule {
+    /// The (row, column) offsets of every neighbour, excluding the cell itself.
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let r = self.radius as isize;
+        let within = |dr: isize, dc: isize| match self.neighbourhood {
+            Neighbourhood::VonNeumann => dr.abs() + dc.abs() <= r,
+            _ => true,
+        };
+        match &self.neighbourhood {
+            Neighbourhood::Custom(offsets) => offsets.clone(),
+            _ => (-r..=r)
+                .flat_map(|dr| (-r..=r).map(move |dc| (dr, dc)))
+                .filter(|&(dr, dc)| (dr, dc) != (0, 0) && within(dr, dc))
+                .collect(),
+        }
+    }
 }
 
 /// Count the total number of paper rolls that can be removed by iteratively
 /// removing accessible rolls until no more can be removed.
-fn count_total_removable(grid: &[Vec<char>]) -> usize {
-    let mut grid = grid.to_vec();
-    let rows = grid.len();
-    let cols = grid[0].len();
-    let mut total_removed = 0;
-
-    loop {
-        // Find all currently accessible rolls
-        let mut to_remove = Vec::new();
-        for r in 0..rows {
-            for c in 0..cols {
-                if grid[r][c] == '@' {
-                    let adjacent = count_adjacent_rolls(&grid, r, c);
-                    if adjacent < 4 {
-                        to_remove.push((r, c));
-                    }
-                }
+fn count_total_removable(grid: &[Vec<char>], rule: &Rule) -> usize {
+    peel(&BitGrid::from_grid(grid), rule).total()
+}
+
+/// When each roll came off the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removal {
+    cols: usize,
+    // The round each cell was removed in, flattened row-major; 0 for rolls
+    // that are never removed and for empty cells
+    rounds: Vec<u32>,
+    /// How many rolls were removed in each round; `per_round[0]` is round 1.
+    pub per_round: Vec<usize>,
+}
+
+impl Removal {
+    pub fn total(&self) -> usize {
+        self.per_round.iter().sum()
+    }
+
+    /// The round the roll at (r, c) was removed in, counting from 1, or
+    /// `None` if it's never removed (or there's no roll there).
+    pub fn round(&self, r: usize, c: usize) -> Option<usize> {
+        match self.rounds[r * self.cols + c] {
+            0 => None,
+            round => Some(round as usize),
+        }
+    }
+
+    /// The grid with each removed roll labelled by its round: 1-9, then a-z
+    /// for rounds 10-35 and `*` beyond. Rolls that stay keep their `@`.
+    pub fn render(&self, grid: &[Vec<char>]) -> String {
+        let mut out = String::new();
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                out.push(match self.round(r, c) {
+                    Some(round) => char::from_digit(round as u32, 36).unwrap_or('*'),
+                    None => cell,
+                });
             }
+            out.push('\n');
         }
+        out
+    }
+}
 
-        // If no rolls can be removed, we're done
-        if to_remove.is_empty() {
-            break;
+/// Remove accessible rolls round by round until none are left, recording
+/// the round each one goes in.
+///
+/// Removing a roll only ever lowers its neighbours' counts, so rather than
+/// rescanning the grid every round, keep each roll's neighbour count and a
+/// queue of rolls that have dropped below the threshold. Each roll enters the
+/// queue at most once, so this is O(cells × neighbourhood). The queue is
+/// first-in first-out, so rounds come off in order: a roll that becomes
+/// accessible while round t is being removed goes in round t + 1.
+///
+/// Counts (2 bytes), rounds (4 bytes) and the queue, which can hold every
+/// roll (4 bytes), are flat arrays indexed row-major: about 10 bytes a cell
+/// on top of the bit grid.
+pub fn peel(grid: &BitGrid, rule: &Rule) -> Removal {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let offsets = rule.offsets();
+    assert!(
+        offsets.len() <= u16::MAX as usize,
+        "Too many neighbours to count"
+    );
+    assert!(rows * cols <= u32::MAX as usize, "Too many cells to index");
+    // Custom offsets needn't be symmetric: the rolls that see (r, c) as a
+    // neighbour are the ones at the opposite offsets.
+    let seen_by: Vec<(isize, isize)> = offsets.iter().map(|&(dr, dc)| (-dr, -dc)).collect();
+
+    // Neighbour counts for every roll; the round is set as a roll is queued
+    let mut counts = vec![0u16; rows * cols];
+    let mut rounds = vec![0u32; rows * cols];
+    let mut queue = VecDeque::new();
+    for r in 0..rows {
+        for c in 0..cols {
+            if !grid.get(r, c) {
+                continue;
+            }
+            let i = r * cols + c;
+            counts[i] = neighbours(rows, cols, r, c, &offsets, rule.wrap)
+                .filter(|&(nr, nc)| grid.get(nr, nc))
+                .count() as u16;
+            if (counts[i] as usize) < rule.threshold {
+                rounds[i] = 1;
+                queue.push_back(i as u32);
+            }
         }
+    }
 
-        // Remove all accessible rolls
-        for (r, c) in &to_remove {
-            grid[*r][*c] = '.';
+    let mut per_round: Vec<usize> = Vec::new();
+    while let Some(i) = queue.pop_front() {
+        let i = i as usize;
+        let round = rounds[i];
+        if per_round.len() < round as usize {
+            per_round.push(0);
+        }
+        per_round[round as usize - 1] += 1;
+
+        for (nr, nc) in neighbours(rows, cols, i / cols, i % cols, &seen_by, rule.wrap) {
+            let j = nr * cols + nc;
+            if !grid.get(nr, nc) || rounds[j] != 0 {
+                continue;
+            }
+            counts[j] -= 1;
+            // Queue it the moment it becomes accessible, and never again
+            if counts[j] as usize + 1 == rule.threshold {
+                rounds[j] = round + 1;
+                queue.push_back(j as u32);
+            }
         }
-        total_removed += to_remove.len();
     }
 
-    total_removed
+    Removal {
+        cols,
+        rounds,
+        per_round,
+    }
 }
 
 /// Count the number of paper rolls that can be accessed by a forklift.
-/// A roll is accessible if it has fewer than 4 adjacent rolls (in 8 directions).
-fn count_accessible_rolls(grid: &[Vec<char>]) -> usize {
-    let rows = grid.len();
-    let cols = grid[0].len();
+/// A roll is accessible if it has fewer than `threshold` neighbouring rolls.
+fn count_accessible_rolls(grid: &[Vec<char>], rule: &Rule) -> usize {
+    BitGrid::from_grid(grid).accessible(rule).count_ones()
+}
 
-    let mut count = 0;
+/// The rolls packed one bit per cell, 64 columns to a word, so a whole
+/// round works on 64 cells at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    // Words per row; bits past `cols` in the last word are always zero
+    words: usize,
+    bits: Vec<u64>,
+}
 
-    for r in 0..rows {
-        for c in 0..cols {
-            if grid[r][c] == '@' {
-                let adjacent = count_adjacent_rolls(grid, r, c);
-                if adjacent < 4 {
-                    count += 1;
+impl BitGrid {
+    /// Pack a rectangular grid; panics if the rows differ in width.
+    pub fn from_grid(grid: &[Vec<char>]) -> Self {
+        let rows = grid.len();
+        let cols = grid[0].len();
+        assert!(
+            grid.iter().all(|row| row.len() == cols),
+            "All grid rows must have the same width"
+        );
+        let words = cols.div_ceil(64);
+        let mut bits = vec![0; rows * words];
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                if cell == '@' {
+                    bits[r * words + c / 64] |= 1 << (c % 64);
                 }
             }
         }
+        BitGrid {
+            rows,
+            cols,
+            words,
+            bits,
+        }
     }
 
-    count
-}
+    /// Whether there's a roll at (r, c).
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        self.bits[r * self.words + c / 64] >> (c % 64) & 1 == 1
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn row(&self, r: usize) -> &[u64] {
+        &self.bits[r * self.words..(r + 1) * self.words]
+    }
+
+    /// The rolls with fewer than `threshold` neighbouring rolls.
+    pub fn accessible(&self, rule: &Rule) -> BitGrid {
+        let offsets = rule.offsets();
+        assert!(offsets.len() < 1 << PLANES, "Too many neighbours to count");
+        let mut bits = vec![0; self.bits.len()];
+        for (r, row) in bits.chunks_mut(self.words).enumerate() {
+            for (w, word) in row.iter_mut().enumerate() {
+                *word = self.accessible_word(r, w, &offsets, rule);
+            }
+        }
+        BitGrid { bits, ..*self }
+    }
+
+    /// Remove every accessible roll, round after round, until none are left.
+    /// Returns how many rolls each round removed.
+    ///
+    /// Only words that can see a roll removed in the previous round can
+    /// change, so the later rounds, which peel a thin fringe, only revisit
+    /// those.
+    pub fn peel_rounds(&self, rule: &Rule) -> Vec<usize> {
+        let offsets = rule.offsets();
+        assert!(offsets.len() < 1 << PLANES, "Too many neighbours to count");
+        let mut grid = self.clone();
+        let mut dirty: Vec<(usize, usize)> = (0..self.rows)
+            .flat_map(|r| (0..self.words).map(move |w| (r, w)))
+            .collect();
+        let mut queued = vec![false; self.bits.len()];
+        let mut per_round = Vec::new();
 
-/// Count the number of adjacent paper rolls (8 directions) for a given position.
-fn count_adjacent_rolls(grid: &[Vec<char>], row: usize, col: usize) -> usize {
-    let rows = grid.len() as i32;
-    let cols = grid[0].len() as i32;
-    let r = row as i32;
-    let c = col as i32;
+        loop {
+            // Decide the whole round against the same grid before removing anything
+            let removed: Vec<(usize, usize, u64)> = dirty
+                .iter()
+                .map(|&(r, w)| (r, w, grid.accessible_word(r, w, &offsets, rule)))
+                .filter(|&(_, _, x)| x != 0)
+                .collect();
+            if removed.is_empty() {
+                return per_round;
+            }
 
-    let directions = [
-        (-1, -1), (-1, 0), (-1, 1),
-        ( 0, -1),          ( 0, 1),
-        ( 1, -1), ( 1, 0), ( 1, 1),
-    ];
+            for &(r, w) in &dirty {
+                queued[r * self.words + w] = false;
+            }
+            dirty.clear();
+            for &(r, w, x) in &removed {
+                grid.bits[r * self.words + w] &= !x;
 
-    let mut count = 0;
-    for (dr, dc) in directions {
-        let nr = r + dr;
-        let nc = c + dc;
-        if nr >= 0 && nr < rows && nc >= 0 && nc < cols {
-            if grid[nr as usize][nc as usize] == '@' {
-                count += 1;
+                // The words that read word w of row r through some offset
+                for &(dr, dc) in &offsets {
+                    let Some(seen_by) = self.offset_row(r, -dr, rule.wrap) else {
+                        continue;
+                    };
+                    let reach = dc.unsigned_abs() / 64 + 1;
+                    let span = if rule.wrap {
+                        0..self.words
+                    } else {
+                        w.saturating_sub(reach)..(w + reach + 1).min(self.words)
+                    };
+                    for j in span {
+                        let i = seen_by * self.words + j;
+                        if !queued[i] {
+                            queued[i] = true;
+                            dirty.push((seen_by, j));
+                        }
+                    }
+                }
             }
+            per_round.push(
+                removed
+                    .iter()
+                    .map(|(_, _, x)| x.count_ones() as usize)
+                    .sum(),
+            );
+        }
+    }
+
+    /// The accessible rolls in word `w` of row `r`.
+    ///
+    /// Each offset's row is shifted into line and added into a bit-sliced
+    /// counter: `planes[i]` holds bit i of the count for each of the word's
+    /// 64 cells, so one ripple-carry add counts 64 cells at once.
+    fn accessible_word(&self, r: usize, w: usize, offsets: &[(isize, isize)], rule: &Rule) -> u64 {
+        // Only as many planes as the largest possible count needs
+        let planes_needed = (usize::BITS - offsets.len().leading_zeros()) as usize;
+        let mut planes = [0u64; PLANES];
+        for &(dr, dc) in offsets {
+            let Some(nr) = self.offset_row(r, dr, rule.wrap) else {
+                continue;
+            };
+            let mut carry = self.shifted_word(nr, w, dc, rule.wrap);
+            for plane in &mut planes[..planes_needed] {
+                let sum = *plane ^ carry;
+                carry &= *plane;
+                *plane = sum;
+            }
+        }
+        self.bits[r * self.words + w] & less_than(&planes[..planes_needed], rule.threshold)
+    }
+
+    /// The row `dr` away from `r`, if it's on the grid.
+    fn offset_row(&self, r: usize, dr: isize, wrap: bool) -> Option<usize> {
+        if wrap {
+            Some((r as isize + dr).rem_euclid(self.rows as isize) as usize)
+        } else {
+            r.checked_add_signed(dr).filter(|&nr| nr < self.rows)
+        }
+    }
+
+    /// Word `w` of row `r` as seen from `d` columns over: bit c is the cell at
+    /// column c + d, with columns off the edge either empty or wrapped around.
+    ///
+    /// Bits past the last column may pick up neighbours; they never matter,
+    /// since the counts are masked by the row's own rolls, whose padding is clear.
+    fn shifted_word(&self, r: usize, w: usize, d: isize, wrap: bool) -> u64 {
+        let row = self.row(r);
+        if wrap {
+            // Rotating is the OR of two plain shifts, one from each side
+            let d = d.rem_euclid(self.cols as isize);
+            shifted(row, w, d) | shifted(row, w, d - self.cols as isize)
+        } else {
+            shifted(row, w, d)
+        }
+    }
+}
+
+/// Most bit planes a neighbour count can need: up to 65535 neighbours.
+const PLANES: usize = 16;
+
+/// Word `w` of `src` shifted by `d` bits, filling with zeros past either end.
+fn shifted(src: &[u64], w: usize, d: isize) -> u64 {
+    // Bit c of the result is bit 64 * w + c + d of the row
+    let start = 64 * w as isize + d;
+    let word = |i: isize| {
+        if i >= 0 && (i as usize) < src.len() {
+            src[i as usize]
+        } else {
+            0
+        }
+    };
+    let (q, b) = (start.div_euclid(64), start.rem_euclid(64));
+    if b == 0 {
+        word(q)
+    } else {
+        word(q) >> b | word(q + 1) << (64 - b)
+    }
+}
+
+/// Mask of the cells whose bit-sliced count is below `threshold`, comparing
+/// from the top plane down.
+fn less_than(planes: &[u64], threshold: usize) -> u64 {
+    if threshold >> planes.len() > 0 {
+        return u64::MAX;
+    }
+    let mut below = 0;
+    let mut equal = u64::MAX;
+    for (i, plane) in planes.iter().enumerate().rev() {
+        if threshold >> i & 1 == 1 {
+            below |= equal & !plane;
+            equal &= plane;
+        } else {
+            equal &= !plane;
         }
     }
+    below
+}
 
-    count
+/// The positions at the given offsets from a position, either dropping those
+/// off the grid or wrapping them around the edges.
+fn neighbours(
+    rows: usize,
+    cols: usize,
+    row: usize,
+    col: usize,
+    offsets: &[(isize, isize)],
+    wrap: bool,
+) -> impl Iterator<Item = (usize, usize)> {
+    offsets.iter().filter_map(move |&(dr, dc)| {
+        if wrap {
+            let r = (row as isize + dr).rem_euclid(rows as isize);
+            let c = (col as isize + dc).rem_euclid(cols as isize);
+            return Some((r as usize, c as usize));
+        }
+        let r = row.checked_add_signed(dr)?;
+        let c = col.checked_add_signed(dc)?;
+        (r < rows && c < cols).then_some((r, c))
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rng::Lcg;
+
+    /// Count the number of paper rolls at the given offsets from a position.
+    fn count_adjacent_rolls(
+        grid: &[Vec<char>],
+        row: usize,
+        col: usize,
+        offsets: &[(isize, isize)],
+        wrap: bool,
+    ) -> usize {
+        neighbours(grid.len(), grid[0].len(), row, col, offsets, wrap)
+            .filter(|&(r, c)| grid[r][c] == '@')
+            .count()
+    }
 
     #[test]
     fn test_example() {
@@ -119,7 +473,7 @@ mod tests {
             "@.@.@@@.@.",
         ];
         let grid: Vec<Vec<char>> = input.iter().map(|line| line.chars().collect()).collect();
-        assert_eq!(count_accessible_rolls(&grid), 13);
+        assert_eq!(count_accessible_rolls(&grid, &Rule::default()), 13);
     }
 
     #[test]
@@ -137,7 +491,240 @@ mod tests {
             "@.@.@@@.@.",
         ];
         let grid: Vec<Vec<char>> = input.iter().map(|line| line.chars().collect()).collect();
-        assert_eq!(count_total_removable(&grid), 43);
+        assert_eq!(count_total_removable(&grid, &Rule::default()), 43);
+    }
+
+    /// Pseudo-random grid with roughly `density` percent rolls.
+    fn generate(rows: usize, cols: usize, density: u64, seed: u64) -> Vec<Vec<char>> {
+        let mut rng = Lcg(seed);
+        (0..rows)
+            .map(|_| {
+                (0..cols)
+                    .map(|_| if rng.next(100) < density { '@' } else { '.' })
+                    .collect()
+            })
+            .collect()
     }
-}
 
+    /// The original round-by-round removal, rescanning the grid each round.
+    fn count_by_rounds(grid: &[Vec<char>], rule: &Rule) -> usize {
+        let offsets = rule.offsets();
+        let mut grid = grid.to_vec();
+        let mut total = 0;
+        loop {
+            let mut to_remove = Vec::new();
+            for r in 0..grid.len() {
+                for c in 0..grid[0].len() {
+                    if grid[r][c] == '@'
+                        && count_adjacent_rolls(&grid, r, c, &offsets, rule.wrap) < rule.threshold
+                    {
+                        to_remove.push((r, c));
+                    }
+                }
+            }
+            if to_remove.is_empty() {
+                return total;
+            }
+            for &(r, c) in &to_remove {
+                grid[r][c] = '.';
+            }
+            total += to_remove.len();
+        }
+    }
+
+    #[test]
+    fn test_worklist_matches_rounds() {
+        for seed in 0..20 {
+            let grid = generate(40, 30, 60 + seed % 30, seed);
+            let rule = Rule::default();
+            assert_eq!(
+                count_total_removable(&grid, &rule),
+                count_by_rounds(&grid, &rule)
+            );
+        }
+    }
+
+    #[test]
+    fn test_large_grid() {
+        // Four million cells through the worklist itself, each visited a
+        // constant number of times
+        let grid = BitGrid::from_grid(&generate(2000, 2000, 75, 1));
+        let rule = Rule::default();
+        let removal = peel(&grid, &rule);
+        assert!(removal.total() > grid.accessible(&rule).count_ones());
+        assert_eq!(removal.per_round, grid.peel_rounds(&rule));
+    }
+
+    #[test]
+    fn test_offsets() {
+        let rule = |neighbourhood, radius| Rule {
+            neighbourhood,
+            radius,
+            ..Rule::default()
+        };
+        assert_eq!(rule(Neighbourhood::Moore, 1).offsets().len(), 8);
+        assert_eq!(rule(Neighbourhood::Moore, 2).offsets().len(), 24);
+        assert_eq!(rule(Neighbourhood::VonNeumann, 1).offsets().len(), 4);
+        assert_eq!(rule(Neighbourhood::VonNeumann, 2).offsets().len(), 12);
+    }
+
+    #[test]
+    fn test_torus() {
+        // A full 3x3 torus: every roll sees its 4 orthogonal neighbours
+        let grid: Vec<Vec<char>> = vec!["@@@".chars().collect(); 3];
+        let mut rule = Rule {
+            neighbourhood: Neighbourhood::VonNeumann,
+            radius: 1,
+            threshold: 3,
+            wrap: true,
+        };
+        assert_eq!(count_accessible_rolls(&grid, &rule), 0);
+        rule.wrap = false;
+        // Only the corners have fewer than 3 orthogonal neighbours
+        assert_eq!(count_accessible_rolls(&grid, &rule), 4);
+    }
+
+    #[test]
+    fn test_rule_variants_match_rounds() {
+        let rules = [
+            Rule {
+                neighbourhood: Neighbourhood::VonNeumann,
+                radius: 1,
+                threshold: 3,
+                wrap: true,
+            },
+            Rule {
+                neighbourhood: Neighbourhood::Moore,
+                radius: 2,
+                threshold: 12,
+                wrap: false,
+            },
+            // Lopsided: only looks up and to the right
+            Rule {
+                neighbourhood: Neighbourhood::Custom(vec![(-1, 0), (-1, 1), (0, 1), (-2, 2)]),
+                radius: 0,
+                threshold: 2,
+                wrap: false,
+            },
+        ];
+        for (seed, rule) in rules.iter().enumerate() {
+            let grid = generate(30, 25, 70, seed as u64);
+            let expected = count_by_rounds(&grid, rule);
+            assert_eq!(count_total_removable(&grid, rule), expected, "{:?}", rule);
+            assert_eq!(
+                peel(&BitGrid::from_grid(&grid), rule).total(),
+                expected,
+                "{:?}",
+                rule
+            );
+        }
+    }
+
+    #[test]
+    fn test_removal_rounds() {
+        let input = [
+            "..@@.@@@@.",
+            "@@@.@.@.@@",
+            "@@@@@.@.@@",
+            "@.@@@@..@.",
+            "@@.@@@@.@@",
+            ".@@@@@@@.@",
+            ".@.@.@.@@@",
+            "@.@@@.@@@@",
+            ".@@@@@@@@.",
+            "@.@.@@@.@.",
+        ];
+        let grid: Vec<Vec<char>> = input.iter().map(|line| line.chars().collect()).collect();
+        let removal = peel(&BitGrid::from_grid(&grid), &Rule::default());
+
+        assert_eq!(removal.per_round, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(removal.total(), 43);
+        assert_eq!(removal.round(0, 2), Some(1));
+        assert_eq!(removal.round(0, 0), None);
+
+        let rendered = removal.render(&grid);
+        assert_eq!(rendered.lines().next(), Some("..11.1121."));
+        // Never-removed rolls keep their '@'
+        assert_eq!(rendered.matches('@').count(), 71 - 43);
+    }
+
+    #[test]
+    fn test_rounds_match_synchronous_removal() {
+        for seed in 0..10 {
+            let grid = generate(30, 30, 70, seed);
+            let rule = Rule::default();
+            let removal = peel(&BitGrid::from_grid(&grid), &rule);
+            let offsets = rule.offsets();
+
+            // Replay round by round, removing everything accessible at once
+            let mut grid = grid.clone();
+            for (i, &expected) in removal.per_round.iter().enumerate() {
+                let accessible: Vec<(usize, usize)> = (0..30)
+                    .flat_map(|r| (0..30).map(move |c| (r, c)))
+                    .filter(|&(r, c)| {
+                        grid[r][c] == '@' && count_adjacent_rolls(&grid, r, c, &offsets, false) < 4
+                    })
+                    .collect();
+                assert_eq!(accessible.len(), expected);
+                for &(r, c) in &accessible {
+                    assert_eq!(removal.round(r, c), Some(i + 1));
+                    grid[r][c] = '.';
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "same width")]
+    fn test_bitgrid_rejects_ragged_rows() {
+        let grid = vec![vec!['@'; 10], vec!['@'; 70]];
+        BitGrid::from_grid(&grid);
+    }
+
+    #[test]
+    fn test_bitgrid_matches_worklist() {
+        // Wider than a word and not a multiple of 64, so shifts cross words
+        // and the padding bits matter
+        let rules = [
+            Rule::default(),
+            Rule {
+                neighbourhood: Neighbourhood::VonNeumann,
+                radius: 1,
+                threshold: 3,
+                wrap: true,
+            },
+            Rule {
+                neighbourhood: Neighbourhood::Moore,
+                radius: 2,
+                threshold: 13,
+                wrap: true,
+            },
+            Rule {
+                neighbourhood: Neighbourhood::Custom(vec![(0, 70), (0, -65), (1, 64), (-1, -1)]),
+                radius: 0,
+                threshold: 2,
+                wrap: false,
+            },
+        ];
+        for (seed, rule) in rules.iter().enumerate() {
+            let grid = generate(40, 150, 65, seed as u64);
+            let bits = BitGrid::from_grid(&grid);
+            let offsets = rule.offsets();
+            let accessible = (0..40)
+                .flat_map(|r| (0..150).map(move |c| (r, c)))
+                .filter(|&(r, c)| {
+                    grid[r][c] == '@'
+                        && count_adjacent_rolls(&grid, r, c, &offsets, rule.wrap) < rule.threshold
+                })
+                .count();
+
+            assert_eq!(bits.accessible(rule).count_ones(), accessible, "{:?}", rule);
+            assert_eq!(
+                bits.peel_rounds(rule),
+                peel(&BitGrid::from_grid(&grid), rule).per_round,
+                "{:?}",
+                rule
+            );
+        }
+    }
+}
@@ -1,134 +1,332 @@
-use crate::read_input;
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::parse::{ParseError, Sections};
+use crate::{read_sections, write_debug};
 
 pub fn run() {
-    let input = read_input("day_05");
+    let input = read_sections("day_05");
 
-    let (ranges, ingredients) = parse_input(&input);
+    let sourced = parse_ranges(&input).unwrap();
+    let ranges: Vec<IdRange> = sourced.iter().map(|s| s.range).collect();
+    // Parsed line by line from the loaded input, without building a Vec<u64>
+    let ingredients = || input.parse_iter(1).unwrap().map(Result::unwrap);
 
-    // Part 1: Count fresh ingredients
-    let res01 = count_fresh(&ranges, &ingredients);
+    // Part 1: Count fresh ingredients, checking each ID as its line is parsed
+    let res01 = count_fresh(&ranges, ingredients());
 
     // Part 2: Count total unique IDs considered fresh by all ranges
     let res02 = count_total_fresh_ids(&ranges);
 
     println!("Part 1 answer: {}", res01);
     println!("Part 2 answer: {}", res02);
+
+    // Set AOC_DEBUG_DIR to write a report of how the ranges overlap
+    // (part 1 has already parsed every ingredient line, so these can't fail)
+    write_debug("day_05_report.txt", || {
+        OverlapReport::new(&sourced, ingredients()).to_string()
+    });
+}
+
+/// An inclusive range of fresh ingredient IDs.
+type IdRange = (u64, u64);
+
+/// A range as written in the input, with the line it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceRange {
+    pub line: usize,
+    pub range: IdRange,
 }
 
-/// Parse the input into ranges and ingredient IDs.
-/// The input consists of ranges (start-end) until a blank line,
-/// then individual ingredient IDs.
-fn parse_input(input: &[String]) -> (Vec<(u64, u64)>, Vec<u64>) {
-    let mut ranges: Vec<(u64, u64)> = Vec::new();
-    let mut ingredients: Vec<u64> = Vec::new();
-    let mut parsing_ranges = true;
-
-    for line in input {
-        if line.is_empty() {
-            parsing_ranges = false;
-            continue;
+impl fmt::Display for SourceRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} ({}-{})", self.line, self.range.0, self.range.1)
+    }
+}
+
+/// Parse the first section's ranges, keeping their line numbers.
+fn parse_ranges(input: &Sections) -> Result<Vec<SourceRange>, ParseError> {
+    // Parse ranges like "3-5"
+    input.parse_with(0, |c| {
+        let line = c.line();
+        let at_start = c.skip_ws().clone();
+        let start = c.int()?;
+        c.tag("-")?;
+        let end = c.int()?;
+        if end < start {
+            return Err(at_start.error(format!("range {}-{} is reversed", start, end)));
         }
+        Ok(SourceRange {
+            line,
+            range: (start, end),
+        })
+    })
+}
+
+/// Count how many ingredient IDs are fresh.
+fn count_fresh(ranges: &[IdRange], ingredients: impl IntoIterator<Item = u64>) -> usize {
+    RangeIndex::new(ranges).count_fresh(ingredients)
+}
+
+/// Count the total number of unique IDs considered fresh by merging overlapping ranges.
+fn count_total_fresh_ids(ranges: &[IdRange]) -> u128 {
+    RangeIndex::new(ranges).total()
+}
+
+/// The fresh ranges merged once into sorted, disjoint, non-adjacent ranges,
+/// so each lookup is a binary search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeIndex {
+    merged: Vec<IdRange>,
+}
+
+impl RangeIndex {
+    /// Panics on a reversed range; [`parse_ranges`] rejects those up front.
+    pub fn new(ranges: &[IdRange]) -> Self {
+        assert!(
+            ranges.iter().all(|&(start, end)| start <= end),
+            "Ranges must not be reversed"
+        );
+
+        // Sort ranges by start value
+        let mut sorted_ranges: Vec<IdRange> = ranges.to_vec();
+        sorted_ranges.sort_by_key(|r| r.0);
 
-        if parsing_ranges {
-            // Parse range like "3-5"
-            let parts: Vec<&str> = line.split('-').collect();
-            let start: u64 = parts[0].parse().unwrap();
-            let end: u64 = parts[1].parse().unwrap();
-            ranges.push((start, end));
-        } else {
-            // Parse ingredient ID
-            let id: u64 = line.parse().unwrap();
-            ingredients.push(id);
+        // Merge overlapping ranges
+        let mut merged: Vec<IdRange> = Vec::new();
+        for (start, end) in sorted_ranges {
+            match merged.last_mut() {
+                // Ranges overlap or are adjacent, merge them
+                // (a range ending at u64::MAX has nothing after it to be apart from)
+                Some(last) if last.1.checked_add(1).is_none_or(|next| start <= next) => {
+                    last.1 = last.1.max(end)
+                }
+                // No overlap, add new range
+                _ => merged.push((start, end)),
+            }
         }
+
+        RangeIndex { merged }
+    }
+
+    /// The merged ranges, in increasing order.
+    pub fn ranges(&self) -> &[IdRange] {
+        &self.merged
+    }
+
+    /// Whether the ID falls within any range, in O(log ranges).
+    pub fn contains(&self, id: u64) -> bool {
+        // The first merged range that doesn't end before the ID
+        let i = self.merged.partition_point(|&(_, end)| end < id);
+        self.merged.get(i).is_some_and(|&(start, _)| start <= id)
     }
 
-    (ranges, ingredients)
+    /// Total number of IDs covered by the ranges.
+    ///
+    /// A u128, since 0-18446744073709551615 alone holds 2^64 IDs.
+    pub fn total(&self) -> u128 {
+        self.merged
+            .iter()
+            .map(|&(start, end)| (end - start) as u128 + 1)
+            .sum()
+    }
+
+    /// Lazily keep the fresh IDs from a stream, checking each as it arrives,
+    /// so the IDs never need to be held in memory together.
+    pub fn fresh<'a, I>(&'a self, ids: I) -> impl Iterator<Item = u64> + 'a
+    where
+        I: IntoIterator<Item = u64>,
+        I::IntoIter: 'a,
+    {
+        ids.into_iter().filter(|&id| self.contains(id))
+    }
+
+    /// Count the fresh IDs in a stream.
+    pub fn count_fresh(&self, ids: impl IntoIterator<Item = u64>) -> usize {
+        self.fresh(ids).count()
+    }
 }
 
-/// Check if an ingredient ID is fresh (falls within any range).
-fn is_fresh(id: u64, ranges: &[(u64, u64)]) -> bool {
-    for &(start, end) in ranges {
-        if id >= start && id <= end {
-            return true;
+/// How the input ranges relate to each other and to the ingredients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlapReport {
+    /// Pairs of ranges that partly overlap, neither containing the other.
+    pub overlaps: Vec<(SourceRange, SourceRange)>,
+    /// (inner, outer) pairs where inner lies entirely within outer. Of two
+    /// identical ranges, the later line counts as the inner one.
+    pub contained: Vec<(SourceRange, SourceRange)>,
+    /// Ranges the others cover between them, so any one of these could be
+    /// dropped without changing which IDs are fresh.
+    pub redundant: Vec<SourceRange>,
+    /// The IDs between consecutive merged ranges.
+    pub gaps: Vec<IdRange>,
+    /// Each ingredient with the lines of the ranges covering it.
+    pub coverage: Vec<(u64, Vec<usize>)>,
+}
+
+impl OverlapReport {
+    pub fn new(ranges: &[SourceRange], ingredients: impl IntoIterator<Item = u64>) -> Self {
+        // By start, longest first, so a range only meets later ranges that start
+        // inside it, and ties with an identical range go to the earlier line
+        let mut sorted = ranges.to_vec();
+        sorted.sort_by_key(|s| (s.range.0, Reverse(s.range.1), s.line));
+
+        let mut overlaps = Vec::new();
+        let mut contained = Vec::new();
+        for (i, outer) in sorted.iter().enumerate() {
+            for other in sorted[i + 1..]
+                .iter()
+                .take_while(|o| o.range.0 <= outer.range.1)
+            {
+                if other.range.1 <= outer.range.1 {
+                    contained.push((*other, *outer));
+                } else {
+                    overlaps.push((*outer, *other));
+                }
+            }
+        }
+
+        let redundant = sorted
+            .iter()
+            .enumerate()
+            .filter(|&(i, s)| {
+                let others = sorted.iter().enumerate().filter(|&(j, _)| j != i);
+                covers(others.map(|(_, o)| o.range), s.range)
+            })
+            .map(|(_, s)| *s)
+            .collect();
+
+        let plain: Vec<IdRange> = ranges.iter().map(|s| s.range).collect();
+        let gaps = RangeIndex::new(&plain)
+            .ranges()
+            .windows(2)
+            .map(|w| (w[0].1 + 1, w[1].0 - 1))
+            .collect();
+
+        // A linear scan per ingredient; fine for a diagnostic
+        let coverage = ingredients
+            .into_iter()
+            .map(|id| {
+                let mut lines: Vec<usize> = ranges
+                    .iter()
+                    .filter(|s| s.range.0 <= id && id <= s.range.1)
+                    .map(|s| s.line)
+                    .collect();
+                lines.sort_unstable();
+                (id, lines)
+            })
+            .collect();
+
+        OverlapReport {
+            overlaps,
+            contained,
+            redundant,
+            gaps,
+            coverage,
         }
     }
-    false
 }
 
-/// Count how many ingredient IDs are fresh.
-fn count_fresh(ranges: &[(u64, u64)], ingredients: &[u64]) -> usize {
-    ingredients
-        .iter()
-        .filter(|&&id| is_fresh(id, ranges))
-        .count()
+/// Whether `ranges`, given in order of start, cover every ID in `target`.
+fn covers(ranges: impl Iterator<Item = IdRange>, target: IdRange) -> bool {
+    // The first ID not yet covered; u128 so it can step past u64::MAX
+    let mut next = target.0 as u128;
+    for (start, end) in ranges {
+        if start as u128 > next {
+            break;
+        }
+        next = next.max(end as u128 + 1);
+    }
+    next > target.1 as u128
 }
 
-/// Count the total number of unique IDs considered fresh by merging overlapping ranges.
-fn count_total_fresh_ids(ranges: &[(u64, u64)]) -> u64 {
-    if ranges.is_empty() {
-        return 0;
-    }
-
-    // Sort ranges by start value
-    let mut sorted_ranges: Vec<(u64, u64)> = ranges.to_vec();
-    sorted_ranges.sort_by_key(|r| r.0);
-
-    // Merge overlapping ranges
-    let mut merged: Vec<(u64, u64)> = Vec::new();
-    merged.push(sorted_ranges[0]);
-
-    for &(start, end) in &sorted_ranges[1..] {
-        let last = merged.last_mut().unwrap();
-        if start <= last.1 + 1 {
-            // Ranges overlap or are adjacent, merge them
-            last.1 = last.1.max(end);
-        } else {
-            // No overlap, add new range
-            merged.push((start, end));
+impl fmt::Display for OverlapReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (a, b) in &self.overlaps {
+            writeln!(f, "Overlap: {} and {}", a, b)?;
+        }
+        for (inner, outer) in &self.contained {
+            writeln!(f, "Contained: {} inside {}", inner, outer)?;
+        }
+        for s in &self.redundant {
+            writeln!(f, "Redundant: {}", s)?;
         }
+        for (start, end) in &self.gaps {
+            writeln!(f, "Gap: {}-{}", start, end)?;
+        }
+        for (id, lines) in &self.coverage {
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            if lines.is_empty() {
+                writeln!(f, "Ingredient {}: spoiled", id)?;
+            } else {
+                writeln!(f, "Ingredient {}: lines {}", id, lines.join(", "))?;
+            }
+        }
+        Ok(())
     }
-
-    // Count total IDs in merged ranges
-    merged.iter().map(|(start, end)| end - start + 1).sum()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Parse the input into ranges and ingredient IDs.
+    /// The first section holds ranges (start-end), the second individual ingredient IDs.
+    fn parse_input(input: &Sections) -> Result<(Vec<IdRange>, Vec<u64>), ParseError> {
+        let ranges = parse_ranges(input)?.iter().map(|s| s.range).collect();
+        let ingredients = input.parse(1)?;
+
+        Ok((ranges, ingredients))
+    }
+
     #[test]
     fn test_example() {
-        let input: Vec<String> = vec![
-            "3-5",
-            "10-14",
-            "16-20",
-            "12-18",
-            "",
-            "1",
-            "5",
-            "8",
-            "11",
-            "17",
-            "32",
-        ]
-        .into_iter()
-        .map(String::from)
-        .collect();
-
-        let (ranges, ingredients) = parse_input(&input);
-        assert_eq!(count_fresh(&ranges, &ingredients), 3);
+        let input = Sections::new("3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32");
+
+        let (ranges, ingredients) = parse_input(&input).unwrap();
+        assert_eq!(count_fresh(&ranges, ingredients.iter().copied()), 3);
     }
 
     #[test]
     fn test_is_fresh() {
-        let ranges = vec![(3, 5), (10, 14), (16, 20), (12, 18)];
+        let index = RangeIndex::new(&[(3, 5), (10, 14), (16, 20), (12, 18)]);
+
+        assert!(!index.contains(1)); // spoiled
+        assert!(index.contains(5)); // fresh (3-5)
+        assert!(!index.contains(8)); // spoiled
+        assert!(index.contains(11)); // fresh (10-14)
+        assert!(index.contains(17)); // fresh (16-20 and 12-18)
+        assert!(!index.contains(32)); // spoiled
+    }
+
+    #[test]
+    fn test_range_index() {
+        let index = RangeIndex::new(&[(3, 5), (10, 14), (16, 20), (12, 18), (6, 6)]);
+        // 3-5 and 6-6 are adjacent, so they merge too
+        assert_eq!(index.ranges(), &[(3, 6), (10, 20)]);
+        for id in 0..25 {
+            assert_eq!(
+                index.contains(id),
+                (3..=6).contains(&id) || (10..=20).contains(&id)
+            );
+        }
+        assert!(!RangeIndex::new(&[]).contains(0));
+    }
 
-        assert!(!is_fresh(1, &ranges)); // spoiled
-        assert!(is_fresh(5, &ranges)); // fresh (3-5)
-        assert!(!is_fresh(8, &ranges)); // spoiled
-        assert!(is_fresh(11, &ranges)); // fresh (10-14)
-        assert!(is_fresh(17, &ranges)); // fresh (16-20 and 12-18)
-        assert!(!is_fresh(32, &ranges)); // spoiled
+    #[test]
+    fn test_streaming() {
+        use std::io::{BufRead, Cursor};
+
+        // IDs are checked one line at a time as they're read
+        let index = RangeIndex::new(&[(3, 5), (10, 14), (16, 20), (12, 18)]);
+        let reader = Cursor::new("1\n5\n8\n11\n17\n32\n");
+        let ids = reader.lines().map(|line| line.unwrap().parse().unwrap());
+        assert_eq!(index.fresh(ids).collect::<Vec<_>>(), vec![5, 11, 17]);
+
+        // A million IDs, generated on the fly and never stored
+        let index = RangeIndex::new(&[(0, 99), (1_000, 1_099)]);
+        let ids = (0..1_000_000u64).map(|i| i % 2_000);
+        assert_eq!(index.count_fresh(ids), 100_000);
     }
 
     #[test]
@@ -138,5 +336,62 @@ mod tests {
         let ranges = vec![(3, 5), (10, 14), (16, 20), (12, 18)];
         assert_eq!(count_total_fresh_ids(&ranges), 14);
     }
-}
 
+    #[test]
+    fn test_overlap_report() {
+        let input = Sections::new("3-5\n10-14\n16-20\n12-18\n13-14\n\n1\n5\n8\n11\n17\n32");
+        let ranges = parse_ranges(&input).unwrap();
+        let (_, ingredients) = parse_input(&input).unwrap();
+        let report = OverlapReport::new(&ranges, ingredients);
+
+        let lines = |pairs: &[(SourceRange, SourceRange)]| -> Vec<(usize, usize)> {
+            pairs.iter().map(|(a, b)| (a.line, b.line)).collect()
+        };
+        assert_eq!(lines(&report.overlaps), vec![(2, 4), (4, 3)]);
+        assert_eq!(lines(&report.contained), vec![(5, 2), (5, 4)]);
+        // 13-14 adds nothing; 12-18 alone covers 15
+        let redundant: Vec<usize> = report.redundant.iter().map(|s| s.line).collect();
+        assert_eq!(redundant, vec![5]);
+        assert_eq!(report.gaps, vec![(6, 9)]);
+        assert_eq!(report.coverage[1], (5, vec![1]));
+        assert_eq!(report.coverage[4], (17, vec![3, 4]));
+        assert_eq!(report.coverage[5], (32, vec![]));
+
+        let text = report.to_string();
+        assert!(text.contains("Contained: line 5 (13-14) inside line 2 (10-14)"));
+        assert!(text.contains("Gap: 6-9"));
+        assert!(text.contains("Ingredient 32: spoiled"));
+
+        // Covered only by two other ranges together
+        let ranges = parse_ranges(&Sections::new("1-5\n3-8\n6-10\n\n1")).unwrap();
+        let report = OverlapReport::new(&ranges, []);
+        assert_eq!(report.redundant, vec![ranges[1]]);
+        assert!(report.contained.is_empty());
+    }
+
+    #[test]
+    fn test_reversed_range() {
+        let err = parse_input(&Sections::new("3-5\n10-14\n  5-3\n\n1")).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.message, "range 5-3 is reversed");
+    }
+
+    #[test]
+    fn test_full_u64_domain() {
+        const MAX: u64 = u64::MAX;
+
+        // Every ID at once: 2^64 doesn't fit in a u64
+        assert_eq!(count_total_fresh_ids(&[(0, MAX)]), 1 << 64);
+
+        // Touching ranges at the very top still merge, without overflowing
+        let index = RangeIndex::new(&[(MAX, MAX), (MAX - 5, MAX - 1), (0, 0)]);
+        assert_eq!(index.ranges(), &[(0, 0), (MAX - 5, MAX)]);
+        assert_eq!(index.total(), 7);
+        assert!(index.contains(MAX) && !index.contains(MAX - 6));
+
+        let input = Sections::new(format!("{}-{}\n\n{}", MAX - 1, MAX, MAX));
+        let (ranges, ingredients) = parse_input(&input).unwrap();
+        assert_eq!(count_fresh(&ranges, ingredients.iter().copied()), 1);
+        assert_eq!(count_total_fresh_ids(&ranges), 2);
+    }
+}
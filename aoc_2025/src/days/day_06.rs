@@ -1,12 +1,16 @@
+use std::fmt;
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
 
+use crate::parse::ParseError;
+
 pub fn run() {
     // Read input without trimming to preserve column positions
     let path = "../input/2025/day_06.txt";
     let content = fs::read_to_string(Path::new(&path))
-        .expect(&format!("Could not read file: {}", path));
-    
+        .unwrap_or_else(|_| panic!("Could not read file: {}", path));
+
     // Split into lines, keeping each line's content (but removing trailing newlines)
     let input: Vec<String> = content
         .lines()
@@ -26,231 +30,363 @@ pub fn run() {
 
 /// Parse the worksheet and compute the grand total of all problems.
 fn solve_worksheet(input: &[String]) -> u64 {
-    if input.is_empty() {
-        return 0;
-    }
-
-    // The last non-empty line contains the operators
-    let operator_line = input.last().unwrap();
-    let number_lines: Vec<&String> = input.iter().take(input.len() - 1).collect();
-
-    // Find the maximum width of all lines
-    let max_width = input.iter().map(|l| l.len()).max().unwrap_or(0);
-
-    // Pad all lines to the same width for easier processing
-    let padded_number_lines: Vec<String> = number_lines
-        .iter()
-        .map(|l| format!("{:<width$}", l, width = max_width))
-        .collect();
-
-    let padded_operator_line = format!("{:<width$}", operator_line, width = max_width);
-
-    // Combine all lines for separator detection (problems are separated by columns 
-    // that are spaces in ALL rows, including the operator row)
-    let mut all_lines: Vec<String> = padded_number_lines.clone();
-    all_lines.push(padded_operator_line.clone());
-
-    // Find column groups - problems are separated by columns that are all spaces
-    let mut problems: Vec<(Vec<u64>, char)> = Vec::new();
-    let mut col = 0;
+    Worksheet::parse(input)
+        .unwrap()
+        .solve(ReadingMode::Horizontal)
+        .unwrap()
+}
 
-    while col < max_width {
-        // Skip separator columns (all spaces in ALL rows including operator)
-        while col < max_width && is_separator_column(&all_lines, col) {
-            col += 1;
-        }
+/// Parse the worksheet with vertical reading (Part 2).
+/// Each column within a problem is a separate number, read top-to-bottom.
+fn solve_worksheet_vertical(input: &[String]) -> u64 {
+    Worksheet::parse(input)
+        .unwrap()
+        .solve(ReadingMode::VerticalTopDown)
+        .unwrap()
+}
 
-        if col >= max_width {
-            break;
-        }
+/// How the digits of a problem's column block are read into operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingMode {
+    /// One operand per row, digits read left to right.
+    Horizontal,
+    /// One operand per column, rightmost column first, topmost digit most
+    /// significant; the order matters for `-`, `/` and infix problems.
+    RightToLeft,
+    /// One operand per column, topmost digit most significant.
+    VerticalTopDown,
+    /// One operand per column, bottommost digit most significant.
+    VerticalBottomUp,
+}
 
-        // Find the end of this problem (next separator or end of line)
-        let start_col = col;
-        while col < max_width && !is_separator_column(&all_lines, col) {
-            col += 1;
-        }
-        let end_col = col;
+/// A binary operator from the operator row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Max,
+    Min,
+    Pow,
+}
 
-        // Extract numbers from this column range
-        let numbers = extract_numbers(&padded_number_lines, start_col, end_col);
+impl Operator {
+    const TOKENS: [(&'static str, Operator); 7] = [
+        ("+", Operator::Add),
+        ("-", Operator::Sub),
+        ("*", Operator::Mul),
+        ("/", Operator::Div),
+        ("max", Operator::Max),
+        ("min", Operator::Min),
+        ("^", Operator::Pow),
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        Self::TOKENS.iter().find(|(_, op)| *op == self).unwrap().0
+    }
 
-        // Only process if we found numbers
-        if !numbers.is_empty() {
-            // Extract operator from this column range
-            let operator = extract_operator(&padded_operator_line, start_col, end_col);
-            problems.push((numbers, operator));
+    /// Binding strength in infix expressions: `^` binds tightest, `max`/`min` loosest.
+    fn precedence(self) -> u8 {
+        match self {
+            Operator::Max | Operator::Min => 0,
+            Operator::Add | Operator::Sub => 1,
+            Operator::Mul | Operator::Div => 2,
+            Operator::Pow => 3,
         }
     }
 
-    // Compute results and sum
-    let mut total: u64 = 0;
-    for (numbers, operator) in problems {
-        let result: u64 = match operator {
-            '+' => numbers.iter().sum(),
-            '*' => numbers.iter().product(),
-            _ => panic!("Unknown operator: {}", operator),
+    /// Apply the operator, failing on overflow, underflow or division by zero.
+    pub fn apply(self, a: u64, b: u64) -> Result<u64, String> {
+        let result = match self {
+            Operator::Add => a.checked_add(b),
+            Operator::Sub => a.checked_sub(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Div if b == 0 => return Err(format!("division by zero in {} / 0", a)),
+            Operator::Div => Some(a / b),
+            Operator::Max => Some(a.max(b)),
+            Operator::Min => Some(a.min(b)),
+            // 0 and 1 stay put under any exponent, even one that doesn't fit in u32
+            Operator::Pow => match u32::try_from(b) {
+                Ok(exp) => a.checked_pow(exp),
+                Err(_) => (a <= 1).then_some(a),
+            },
         };
-        total += result;
+        result.ok_or_else(|| format!("overflow in {} {} {}", a, self, b))
     }
-
-    total
 }
 
-/// Parse the worksheet with vertical reading (Part 2).
-/// Each column within a problem is a separate number, read top-to-bottom.
-fn solve_worksheet_vertical(input: &[String]) -> u64 {
-    if input.is_empty() {
-        return 0;
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
     }
+}
 
-    // The last non-empty line contains the operators
-    let operator_line = input.last().unwrap();
-    let number_lines: Vec<&String> = input.iter().take(input.len() - 1).collect();
-
-    // Find the maximum width of all lines
-    let max_width = input.iter().map(|l| l.len()).max().unwrap_or(0);
-
-    // Pad all lines to the same width for easier processing
-    let padded_number_lines: Vec<String> = number_lines
-        .iter()
-        .map(|l| format!("{:<width$}", l, width = max_width))
-        .collect();
-
-    let padded_operator_line = format!("{:<width$}", operator_line, width = max_width);
-
-    // Combine all lines for separator detection
-    let mut all_lines: Vec<String> = padded_number_lines.clone();
-    all_lines.push(padded_operator_line.clone());
-
-    // Find column groups - problems are separated by columns that are all spaces
-    let mut problems: Vec<(Vec<u64>, char)> = Vec::new();
-    let mut col = 0;
-
-    while col < max_width {
-        // Skip separator columns
-        while col < max_width && is_separator_column(&all_lines, col) {
-            col += 1;
-        }
+/// What a problem does with its operands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// A single operator applied left to right across all operands.
+    Fold(Operator),
+    /// One operator between each pair of operands, evaluated with precedence
+    /// (`^` is right-associative, everything else left-associative).
+    Infix(Vec<Operator>),
+}
 
-        if col >= max_width {
-            break;
-        }
+/// A single problem: its column span, the operands read from it and its operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub columns: Range<usize>,
+    pub operands: Vec<u64>,
+    pub operator: Operation,
+}
 
-        // Find the end of this problem (next separator or end of line)
-        let start_col = col;
-        while col < max_width && !is_separator_column(&all_lines, col) {
-            col += 1;
+impl Problem {
+    pub fn evaluate(&self) -> Result<u64, EvalError> {
+        let error = |message| EvalError {
+            columns: self.columns.clone(),
+            message,
+        };
+        let (&first, rest) = self
+            .operands
+            .split_first()
+            .ok_or_else(|| error("no operands".to_string()))?;
+        match &self.operator {
+            Operation::Fold(op) => rest
+                .iter()
+                .try_fold(first, |acc, &b| op.apply(acc, b))
+                .map_err(error),
+            Operation::Infix(ops) if ops.len() != rest.len() => Err(error(format!(
+                "{} operators for {} operands",
+                ops.len(),
+                self.operands.len()
+            ))),
+            Operation::Infix(ops) => {
+                evaluate_infix(first, ops.iter().copied().zip(rest.iter().copied())).map_err(error)
+            }
         }
-        let end_col = col;
+    }
+}
 
-        // Extract numbers vertically - each column is a separate number
-        let numbers = extract_numbers_vertical(&padded_number_lines, start_col, end_col);
+/// Evaluate `first op1 b1 op2 b2 ...` with operator precedence (shunting-yard).
+fn evaluate_infix(first: u64, terms: impl Iterator<Item = (Operator, u64)>) -> Result<u64, String> {
+    fn reduce(values: &mut Vec<u64>, pending: &mut Vec<Operator>) -> Result<(), String> {
+        let op = pending.pop().unwrap();
+        let b = values.pop().unwrap();
+        let a = values.pop().unwrap();
+        values.push(op.apply(a, b)?);
+        Ok(())
+    }
 
-        // Only process if we found numbers
-        if !numbers.is_empty() {
-            let operator = extract_operator(&padded_operator_line, start_col, end_col);
-            problems.push((numbers, operator));
+    let mut values = vec![first];
+    let mut pending: Vec<Operator> = Vec::new();
+    for (op, b) in terms {
+        while let Some(&top) = pending.last() {
+            let binds_left = top.precedence() > op.precedence()
+                || (top.precedence() == op.precedence() && op != Operator::Pow);
+            if !binds_left {
+                break;
+            }
+            reduce(&mut values, &mut pending)?;
         }
+        pending.push(op);
+        values.push(b);
+    }
+    while !pending.is_empty() {
+        reduce(&mut values, &mut pending)?;
     }
+    Ok(values[0])
+}
 
-    // Compute results and sum
-    let mut total: u64 = 0;
-    for (numbers, operator) in problems {
-        let result: u64 = match operator {
-            '+' => numbers.iter().sum(),
-            '*' => numbers.iter().product(),
-            _ => panic!("Unknown operator: {}", operator),
-        };
-        total += result;
+/// An evaluation failure (overflow, division by zero, ...) with the problem's columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+    pub columns: Range<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "columns {}-{}: {}",
+            self.columns.start + 1,
+            self.columns.end,
+            self.message
+        )
     }
+}
 
-    total
+impl std::error::Error for EvalError {}
+
+/// A worksheet split into problems, which are separated by columns that are
+/// blank in every row (including the operator row).
+///
+/// Columns are counted in chars, and the number rows are kept as one padded
+/// char grid so every pass over the sheet is linear in its size.
+#[derive(Debug, Clone)]
+pub struct Worksheet {
+    cells: Vec<char>,
+    width: usize,
+    height: usize,
+    blocks: Vec<(Range<usize>, Operation)>,
 }
 
-/// Extract numbers vertically - each column in the range is a separate number.
-/// Digits are read top-to-bottom, with the topmost being most significant.
-fn extract_numbers_vertical(lines: &[String], start_col: usize, end_col: usize) -> Vec<u64> {
-    let mut numbers = Vec::new();
-
-    // Process each column individually
-    for col in start_col..end_col {
-        let mut num_str = String::new();
-        
-        // Read digits from top to bottom
-        for line in lines {
-            let ch = line.chars().nth(col).unwrap_or(' ');
-            if ch.is_ascii_digit() {
-                num_str.push(ch);
-            }
+impl Worksheet {
+    /// Parse the number rows followed by the operator row.
+    pub fn parse<S: AsRef<str>>(lines: &[S]) -> Result<Self, ParseError> {
+        let mut grid: Vec<Vec<char>> = lines.iter().map(|l| l.as_ref().chars().collect()).collect();
+        let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+        for row in &mut grid {
+            row.resize(width, ' ');
         }
 
-        // If we found any digits in this column, it's a number
-        if !num_str.is_empty() {
-            if let Ok(num) = num_str.parse::<u64>() {
-                numbers.push(num);
+        let mut blank = vec![true; width];
+        for row in &grid {
+            for (col, &ch) in row.iter().enumerate() {
+                blank[col] &= ch == ' ';
             }
         }
-    }
 
-    numbers
-}
+        let operator_row = grid.pop().unwrap_or_default();
+        let mut sheet = Worksheet {
+            cells: grid.concat(),
+            width,
+            height: grid.len(),
+            blocks: Vec::new(),
+        };
+
+        let mut col = 0;
+        while col < width {
+            if blank[col] {
+                col += 1;
+                continue;
+            }
+            let start_col = col;
+            while col < width && !blank[col] {
+                col += 1;
+            }
 
-/// Check if a column is a separator (all spaces in all lines).
-/// A column is a separator if every line at that position is a space.
-fn is_separator_column(lines: &[String], col: usize) -> bool {
-    for line in lines {
-        if col < line.len() {
-            let ch = line.chars().nth(col).unwrap_or(' ');
-            if ch != ' ' {
-                return false;
+            // Blocks without any digits are not problems
+            let columns = start_col..col;
+            if !(0..sheet.height).any(|r| {
+                sheet.row(r)[columns.clone()]
+                    .iter()
+                    .any(char::is_ascii_digit)
+            }) {
+                continue;
             }
+            let error = |column, message| ParseError {
+                line: lines.len(),
+                column: column + 1,
+                message,
+            };
+            let mut ops = extract_operators(&operator_row[columns.clone()], start_col)
+                .map_err(|(column, message)| error(column, message))?;
+            let operation = match ops.len() {
+                0 => {
+                    let message = format!("no operator for columns {}-{}", start_col + 1, col);
+                    return Err(error(start_col, message));
+                }
+                1 => Operation::Fold(ops.pop().unwrap()),
+                _ => Operation::Infix(ops),
+            };
+            sheet.blocks.push((columns, operation));
         }
+
+        Ok(sheet)
     }
-    true
-}
 
-/// Extract all numbers from the given column range across all number lines.
-fn extract_numbers(lines: &[String], start_col: usize, end_col: usize) -> Vec<u64> {
-    let mut numbers = Vec::new();
+    /// The problems on the worksheet, left to right, read in the given mode.
+    /// A problem fails if one of its operands doesn't fit in a u64.
+    pub fn problems(
+        &self,
+        mode: ReadingMode,
+    ) -> impl Iterator<Item = Result<Problem, EvalError>> + '_ {
+        self.blocks.iter().map(move |(columns, operation)| {
+            Ok(Problem {
+                columns: columns.clone(),
+                operands: self.operands(columns, mode)?,
+                operator: operation.clone(),
+            })
+        })
+    }
 
-    for line in lines {
-        let segment: String = line
-            .chars()
-            .skip(start_col)
-            .take(end_col - start_col)
-            .collect();
+    /// The grand total of all problems read in the given mode.
+    pub fn solve(&self, mode: ReadingMode) -> Result<u64, EvalError> {
+        self.problems(mode).try_fold(0u64, |total, problem| {
+            let problem = problem?;
+            let value = problem.evaluate()?;
+            total.checked_add(value).ok_or_else(|| EvalError {
+                columns: problem.columns,
+                message: format!("grand total overflows adding {}", value),
+            })
+        })
+    }
 
-        // Parse number from segment (ignore spaces)
-        let num_str: String = segment.chars().filter(|c| c.is_ascii_digit()).collect();
-        if !num_str.is_empty() {
-            if let Ok(num) = num_str.parse::<u64>() {
-                numbers.push(num);
-            }
-        }
+    fn row(&self, r: usize) -> &[char] {
+        &self.cells[r * self.width..(r + 1) * self.width]
     }
 
-    numbers
+    fn operands(&self, columns: &Range<usize>, mode: ReadingMode) -> Result<Vec<u64>, EvalError> {
+        let rows = 0..self.height;
+        let cell = |r: usize, c: usize| &self.cells[r * self.width + c];
+        let operands: Result<Vec<u64>, String> = match mode {
+            ReadingMode::Horizontal => rows
+                .filter_map(|r| number(self.row(r)[columns.clone()].iter()))
+                .collect(),
+            ReadingMode::RightToLeft => columns
+                .clone()
+                .rev()
+                .filter_map(|c| number(rows.clone().map(|r| cell(r, c))))
+                .collect(),
+            ReadingMode::VerticalTopDown => columns
+                .clone()
+                .filter_map(|c| number(rows.clone().map(|r| cell(r, c))))
+                .collect(),
+            ReadingMode::VerticalBottomUp => columns
+                .clone()
+                .filter_map(|c| number(rows.clone().rev().map(|r| cell(r, c))))
+                .collect(),
+        };
+        operands.map_err(|message| EvalError {
+            columns: columns.clone(),
+            message,
+        })
+    }
 }
 
-/// Extract the operator from the given column range in the operator line.
-fn extract_operator(operator_line: &str, start_col: usize, end_col: usize) -> char {
-    let segment: String = operator_line
-        .chars()
-        .skip(start_col)
-        .take(end_col - start_col)
-        .collect();
+/// Read the digits among `chars` as one number, first digit most significant.
+/// None if there are no digits, an error if the number doesn't fit in a u64.
+fn number<'a>(chars: impl Iterator<Item = &'a char>) -> Option<Result<u64, String>> {
+    let mut digits = chars.filter_map(|ch| ch.to_digit(10));
+    let first = digits.next()?;
+    let value = digits.try_fold(first as u64, |n, d| {
+        n.checked_mul(10)?.checked_add(d as u64)
+    });
+    Some(value.ok_or_else(|| "operand does not fit in a u64".to_string()))
+}
 
-    // Find the operator character (+ or *)
-    for ch in segment.chars() {
-        if ch == '+' || ch == '*' {
-            return ch;
+/// Tokenize the operators in a block of the operator row starting at `start_col`.
+/// Errors carry the offending column.
+fn extract_operators(segment: &[char], start_col: usize) -> Result<Vec<Operator>, (usize, String)> {
+    let mut operators = Vec::new();
+    let mut i = 0;
+    while i < segment.len() {
+        if segment[i] == ' ' {
+            i += 1;
+            continue;
         }
+        let rest = &segment[i..];
+        let Some((token, op)) = Operator::TOKENS
+            .iter()
+            .find(|(token, _)| rest.iter().copied().take(token.len()).eq(token.chars()))
+        else {
+            return Err((start_col + i, format!("unknown operator '{}'", segment[i])));
+        };
+        operators.push(*op);
+        i += token.len();
     }
-
-    panic!(
-        "No operator found in segment '{}' (cols {}-{})",
-        segment, start_col, end_col
-    );
+    Ok(operators)
 }
 
 #[cfg(test)]
@@ -297,4 +433,187 @@ mod tests {
         // Total = 1058 + 3253600 + 625 + 8544 = 3263827
         assert_eq!(solve_worksheet_vertical(&input), 3263827);
     }
+
+    fn example() -> Worksheet {
+        Worksheet::parse(&[
+            "123 328  51 64 ",
+            " 45 64  387 23 ",
+            "  6 98  215 314",
+            "*   +   *   +  ",
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_problems() {
+        let first = example()
+            .problems(ReadingMode::Horizontal)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            first,
+            Problem {
+                columns: 0..3,
+                operands: vec![123, 45, 6],
+                operator: Operation::Fold(Operator::Mul),
+            }
+        );
+        let columns: Vec<_> = example()
+            .problems(ReadingMode::VerticalTopDown)
+            .map(|p| p.unwrap().columns)
+            .collect();
+        assert_eq!(columns, vec![0..3, 4..7, 8..11, 12..15]);
+    }
+
+    #[test]
+    fn test_reading_modes() {
+        let operands = |mode| example().problems(mode).next().unwrap().unwrap().operands;
+        assert_eq!(operands(ReadingMode::Horizontal), vec![123, 45, 6]);
+        assert_eq!(operands(ReadingMode::RightToLeft), vec![356, 24, 1]);
+        assert_eq!(operands(ReadingMode::VerticalTopDown), vec![1, 24, 356]);
+        assert_eq!(operands(ReadingMode::VerticalBottomUp), vec![1, 42, 653]);
+        assert_eq!(example().solve(ReadingMode::VerticalTopDown), Ok(3263827));
+
+        // Columns 13 and 24: only right to left does the subtraction fit
+        let sheet = Worksheet::parse(&["12", "34", "- "]).unwrap();
+        assert_eq!(sheet.solve(ReadingMode::RightToLeft), Ok(11));
+        assert!(sheet.solve(ReadingMode::VerticalTopDown).is_err());
+    }
+
+    #[test]
+    fn test_missing_operator() {
+        let err = Worksheet::parse(&["12 34", "5  67", "+    "]).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 4));
+        assert!(
+            Worksheet::parse::<&str>(&[])
+                .unwrap()
+                .problems(ReadingMode::Horizontal)
+                .next()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let sheet =
+            Worksheet::parse(&["100 8 3   5   2", " 30 2 9   1   3", "-   / max min ^"]).unwrap();
+        let values: Vec<u64> = sheet
+            .problems(ReadingMode::Horizontal)
+            .map(|p| p.unwrap().evaluate().unwrap())
+            .collect();
+        assert_eq!(values, vec![70, 4, 9, 1, 8]);
+        assert_eq!(sheet.solve(ReadingMode::Horizontal), Ok(92));
+
+        let err = Worksheet::parse(&["12 34", "+  %"]).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+        assert_eq!(err.message, "unknown operator '%'");
+    }
+
+    #[test]
+    fn test_infix() {
+        use Operator::*;
+        let eval = |operands: &[u64], ops: &[Operator]| {
+            Problem {
+                columns: 0..1,
+                operands: operands.to_vec(),
+                operator: Operation::Infix(ops.to_vec()),
+            }
+            .evaluate()
+        };
+        assert_eq!(eval(&[2, 3, 4, 2], &[Add, Mul, Pow]), Ok(50));
+        assert_eq!(eval(&[2, 3, 2], &[Pow, Pow]), Ok(512));
+        assert_eq!(eval(&[10, 3, 2], &[Sub, Sub]), Ok(5));
+        assert_eq!(eval(&[12, 2, 3], &[Div, Mul]), Ok(18));
+        assert_eq!(eval(&[5, 1, 2], &[Max, Add]), Ok(5));
+        assert_eq!(eval(&[5, 1, 2], &[Add, Min]), Ok(2));
+
+        // One operator per gap between operands, so the reading mode matters
+        let sheet = Worksheet::parse(&["123", "456", "+ *"]).unwrap();
+        assert_eq!(sheet.solve(ReadingMode::VerticalTopDown), Ok(914));
+        let err = sheet.solve(ReadingMode::Horizontal).unwrap_err();
+        assert_eq!(err.message, "2 operators for 2 operands");
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let eval = |operands: &[u64], op| {
+            Problem {
+                columns: 4..7,
+                operands: operands.to_vec(),
+                operator: Operation::Fold(op),
+            }
+            .evaluate()
+        };
+        let err = eval(&[u64::MAX, 2], Operator::Mul).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "columns 5-7: overflow in 18446744073709551615 * 2"
+        );
+        assert!(eval(&[1, 2], Operator::Sub).is_err());
+        assert!(eval(&[2, 64], Operator::Pow).is_err());
+        assert_eq!(eval(&[1, u64::MAX], Operator::Pow), Ok(1));
+        assert_eq!(
+            eval(&[7, 0], Operator::Div).unwrap_err().message,
+            "division by zero in 7 / 0"
+        );
+
+        let sheet =
+            Worksheet::parse(&["18446744073709551615 1", "+                    +"]).unwrap();
+        let err = sheet.solve(ReadingMode::Horizontal).unwrap_err();
+        assert_eq!(err.columns, 21..22);
+    }
+
+    #[test]
+    fn test_non_ascii() {
+        // 'é' is two bytes but one column; '×' is not an operator
+        let sheet = Worksheet::parse(&["é12 3", " 45 6", "+   *"]).unwrap();
+        let columns: Vec<_> = sheet
+            .problems(ReadingMode::Horizontal)
+            .map(|p| p.unwrap().columns)
+            .collect();
+        assert_eq!(columns, vec![0..3, 4..5]);
+        assert_eq!(sheet.solve(ReadingMode::Horizontal), Ok(57 + 18));
+        assert_eq!(sheet.solve(ReadingMode::VerticalTopDown), Ok(14 + 25 + 36));
+
+        let err = Worksheet::parse(&["ü1 2", "+  ×"]).unwrap_err();
+        assert_eq!(
+            (err.column, err.message.as_str()),
+            (4, "unknown operator '×'")
+        );
+    }
+
+    #[test]
+    fn test_wide_worksheet() {
+        let block = ["12 ", " 3 ", "+  "];
+        let lines: Vec<String> = block.iter().map(|l| l.repeat(100_000)).collect();
+        let sheet = Worksheet::parse(&lines).unwrap();
+        assert_eq!(sheet.solve(ReadingMode::Horizontal), Ok(15 * 100_000));
+        assert_eq!(sheet.solve(ReadingMode::VerticalBottomUp), Ok(33 * 100_000));
+    }
+
+    #[test]
+    fn test_oversized_operand() {
+        // 20 nines is past u64::MAX; the problem fails instead of dropping it
+        let sheet = Worksheet::parse(&[
+            "99999999999999999999 5",
+            "1                    7",
+            "+                    +",
+        ])
+        .unwrap();
+        let err = sheet.solve(ReadingMode::Horizontal).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "columns 1-20: operand does not fit in a u64"
+        );
+        let mut problems = sheet.problems(ReadingMode::Horizontal);
+        assert!(problems.next().unwrap().is_err());
+        assert_eq!(problems.next().unwrap().unwrap().operands, vec![5, 7]);
+
+        // The same digits read down the columns are small enough
+        assert_eq!(
+            sheet.solve(ReadingMode::VerticalTopDown),
+            Ok(91 + 19 * 9 + 57)
+        );
+    }
 }
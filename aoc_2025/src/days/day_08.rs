@@ -1,8 +1,9 @@
+use crate::parse::{ParseError, parse_lines};
 use crate::read_input;
 
 pub fn run() {
     let input = read_input("day_08");
-    let junctions = parse_junctions(&input);
+    let junctions = parse_junctions(&input).unwrap();
 
     let res01 = product_of_three_largest_circuits(&junctions, 1000);
     let res02 = last_connection_x_product(&junctions);
@@ -87,19 +88,14 @@ impl UnionFind {
 }
 
 /// Parse input lines into junction box positions.
-fn parse_junctions(lines: &[String]) -> Vec<Point3D> {
-    lines
-        .iter()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let parts: Vec<i64> = line.split(',').map(|s| s.trim().parse().unwrap()).collect();
-            Point3D {
-                x: parts[0],
-                y: parts[1],
-                z: parts[2],
-            }
-        })
-        .collect()
+fn parse_junctions(lines: &[String]) -> Result<Vec<Point3D>, ParseError> {
+    parse_lines(lines, |c| {
+        let parts: Vec<i64> = c.separated(",", |c| c.skip_ws().int())?;
+        let [x, y, z] = parts[..] else {
+            return Err(c.error(format!("expected 3 coordinates, found {}", parts.len())));
+        };
+        Ok(Point3D { x, y, z })
+    })
 }
 
 /// Generate sorted pairs of junction indices by distance.
@@ -187,7 +183,7 @@ mod tests {
             .filter(|line| !line.is_empty())
             .map(String::from)
             .collect();
-        parse_junctions(&lines)
+        parse_junctions(&lines).unwrap()
     }
 
     #[test]
@@ -252,4 +248,17 @@ mod tests {
         // Product of X coordinates: 216 * 117 = 25272
         assert_eq!(last_connection_x_product(&junctions), 25272);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let lines: Vec<String> = ["1,2,3", "", "4,99999999999999999999,6", "7,8"]
+            .map(String::from)
+            .to_vec();
+        let err = parse_junctions(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+
+        let err = parse_junctions(&lines[3..]).unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("found 2"));
+    }
 }
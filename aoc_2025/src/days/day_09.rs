@@ -1,8 +1,9 @@
+use crate::parse::{ParseError, parse_lines};
 use crate::read_input;
 
 pub fn run() {
     let input = read_input("day_09");
-    let red_tiles = parse_red_tiles(&input);
+    let red_tiles = parse_red_tiles(&input).unwrap();
 
     let res01 = largest_rectangle_area(&red_tiles);
     let res02 = largest_rectangle_area_with_green(&red_tiles);
@@ -19,18 +20,13 @@ struct Point {
 }
 
 /// Parse input lines into red tile coordinates.
-fn parse_red_tiles(lines: &[String]) -> Vec<Point> {
-    lines
-        .iter()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let parts: Vec<i64> = line.split(',').map(|s| s.trim().parse().unwrap()).collect();
-            Point {
-                x: parts[0],
-                y: parts[1],
-            }
-        })
-        .collect()
+fn parse_red_tiles(lines: &[String]) -> Result<Vec<Point>, ParseError> {
+    parse_lines(lines, |c| {
+        let x = c.skip_ws().int()?;
+        c.skip_ws().tag(",")?;
+        let y = c.skip_ws().int()?;
+        Ok(Point { x, y })
+    })
 }
 
 /// Find the largest rectangle area using any two red tiles as opposite corners.
@@ -305,7 +301,7 @@ mod tests {
             .filter(|line| !line.is_empty())
             .map(String::from)
             .collect();
-        parse_red_tiles(&lines)
+        parse_red_tiles(&lines).unwrap()
     }
 
     #[test]
@@ -344,4 +340,15 @@ mod tests {
         let result = largest_rectangle_area_with_green(&red_tiles);
         assert_eq!(result, 24);
     }
+
+    #[test]
+    fn test_parse_errors() {
+        let lines: Vec<String> = ["7,1", "", "11,x", "2 5"].map(String::from).to_vec();
+        let err = parse_red_tiles(&lines).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 4));
+
+        let err = parse_red_tiles(&lines[3..]).unwrap_err();
+        assert_eq!(err.line, 1);
+        assert!(err.message.contains("expected ','"));
+    }
 }
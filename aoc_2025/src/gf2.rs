@@ -0,0 +1,415 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// A fixed-length vector over GF(2), packed 64 bits to a word.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    pub fn zeros(len: usize) -> Self {
+        BitVec {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    /// A vector of length `len` with the given bits set. Out-of-range indices
+    /// are ignored.
+    pub fn from_indices(len: usize, indices: &[usize]) -> Self {
+        let mut v = BitVec::zeros(len);
+        for &i in indices.iter().filter(|&&i| i < len) {
+            v.set(i, true);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        let mask = 1 << (i % 64);
+        if value {
+            self.words[i / 64] |= mask;
+        } else {
+            self.words[i / 64] &= !mask;
+        }
+    }
+
+    pub fn toggle(&mut self, i: usize) {
+        self.words[i / 64] ^= 1 << (i % 64);
+    }
+
+    /// Add (XOR) another vector of the same length into this one.
+    pub fn xor_assign(&mut self, other: &BitVec) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    /// Number of set bits (the Hamming weight).
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Indices of the set bits, in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.get(i))
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits: String = (0..self.len)
+            .map(|i| if self.get(i) { '1' } else { '0' })
+            .collect();
+        write!(f, "BitVec({})", bits)
+    }
+}
+
+/// Largest search `min_weight` will run: 2^24 solutions or 2^24 reachable states.
+pub const MAX_DIMENSION: usize = 24;
+
+/// Both the solution space and the reachable states are too large for
+/// `min_weight` to enumerate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooManySolutions {
+    pub dimension: usize,
+    pub rank: usize,
+}
+
+impl fmt::Display for TooManySolutions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "solution space has dimension {} and rank {}, both above the limit of {}",
+            self.dimension, self.rank, MAX_DIMENSION
+        )
+    }
+}
+
+impl std::error::Error for TooManySolutions {}
+
+/// All solutions of A·x = t over GF(2): `particular` XOR any combination of
+/// the `nullspace` basis vectors.
+#[derive(Debug, Clone)]
+pub struct Gf2Solution {
+    pub particular: BitVec,
+    pub nullspace: Vec<BitVec>,
+    // The reduced system R·x = t' as bitmasks over its `rank` rows, one per
+    // column plus the target; only kept when rank ≤ MAX_DIMENSION.
+    reduced_columns: Vec<u64>,
+    reduced_target: u64,
+}
+
+impl Gf2Solution {
+    /// Dimension of the solution space; there are 2^dimension solutions.
+    pub fn dimension(&self) -> usize {
+        self.nullspace.len()
+    }
+
+    /// Rank of A; there are 2^rank reachable right-hand sides.
+    pub fn rank(&self) -> usize {
+        self.particular.len() - self.dimension()
+    }
+
+    /// A solution with the fewest set bits.
+    ///
+    /// Enumerates whichever is smaller: the 2^dimension solutions, or the
+    /// 2^rank states reachable by pressing columns (a BFS, so machines with
+    /// many buttons but few lights stay cheap). Fails only when both are
+    /// beyond `MAX_DIMENSION`.
+    pub fn min_weight(&self) -> Result<BitVec, TooManySolutions> {
+        let (dimension, rank) = (self.dimension(), self.rank());
+        if dimension.min(rank) > MAX_DIMENSION {
+            return Err(TooManySolutions { dimension, rank });
+        }
+        if dimension <= rank {
+            Ok(self.walk_nullspace())
+        } else {
+            Ok(self.search_states())
+        }
+    }
+
+    /// Walks the nullspace in Gray code order so each step is a single XOR.
+    fn walk_nullspace(&self) -> BitVec {
+        let mut current = self.particular.clone();
+        let mut best = current.clone();
+        let mut best_weight = current.count_ones();
+
+        for step in 1u64..(1u64 << self.dimension()) {
+            current.xor_assign(&self.nullspace[step.trailing_zeros() as usize]);
+            let weight = current.count_ones();
+            if weight < best_weight {
+                best_weight = weight;
+                best = current.clone();
+            }
+        }
+
+        best
+    }
+
+    /// Breadth-first search from the all-off state of the reduced system, so
+    /// the first time the target is reached uses the fewest columns. The
+    /// visited map only holds the states the search gets to, which stops
+    /// well short of all 2^rank when the target is a few presses away.
+    fn search_states(&self) -> BitVec {
+        // via[state] is the column that first reached it
+        let mut via: HashMap<u64, usize> = HashMap::from([(0, 0)]);
+        let mut queue = VecDeque::from([0u64]);
+        while let Some(state) = queue.pop_front() {
+            if state == self.reduced_target {
+                break;
+            }
+            for (j, &code) in self.reduced_columns.iter().enumerate() {
+                let next = state ^ code;
+                if let Entry::Vacant(entry) = via.entry(next) {
+                    entry.insert(j);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut x = BitVec::zeros(self.particular.len());
+        let mut state = self.reduced_target;
+        while state != 0 {
+            let j = via[&state];
+            x.toggle(j);
+            state ^= self.reduced_columns[j];
+        }
+        x
+    }
+}
+
+/// Solve A·x = t over GF(2), where `columns[j]` is column j of A.
+/// Returns `None` if there is no solution.
+pub fn solve(columns: &[BitVec], target: &BitVec) -> Option<Gf2Solution> {
+    let rows = target.len();
+    let vars = columns.len();
+    assert!(
+        columns.iter().all(|c| c.len() == rows),
+        "Columns must match the target length"
+    );
+
+    // Row r of the augmented matrix holds the coefficients of every variable,
+    // with the right-hand side in bit `vars`.
+    let mut matrix: Vec<BitVec> = (0..rows)
+        .map(|r| {
+            let mut row = BitVec::zeros(vars + 1);
+            for (j, column) in columns.iter().enumerate() {
+                if column.get(r) {
+                    row.set(j, true);
+                }
+            }
+            row.set(vars, target.get(r));
+            row
+        })
+        .collect();
+
+    // Gauss-Jordan elimination; pivot row i holds pivot column pivots[i].
+    let mut pivots = Vec::new();
+    for col in 0..vars {
+        let pivot = pivots.len();
+        let Some(found) = (pivot..rows).find(|&r| matrix[r].get(col)) else {
+            continue;
+        };
+        matrix.swap(pivot, found);
+
+        let pivot_row = matrix[pivot].clone();
+        for (r, row) in matrix.iter_mut().enumerate() {
+            if r != pivot && row.get(col) {
+                row.xor_assign(&pivot_row);
+            }
+        }
+        pivots.push(col);
+    }
+
+    // A zero row with rhs 1 means 0 = 1
+    if matrix[pivots.len()..].iter().any(|row| row.get(vars)) {
+        return None;
+    }
+
+    // Set free variables to 0 for the particular solution
+    let mut particular = BitVec::zeros(vars);
+    for (row, &p) in matrix.iter().zip(&pivots) {
+        particular.set(p, row.get(vars));
+    }
+
+    // One basis vector per free variable: set it, and flip the pivots it feeds
+    let nullspace = (0..vars)
+        .filter(|c| !pivots.contains(c))
+        .map(|f| {
+            let mut v = BitVec::zeros(vars);
+            v.set(f, true);
+            for (row, &p) in matrix.iter().zip(&pivots) {
+                v.set(p, row.get(f));
+            }
+            v
+        })
+        .collect();
+
+    // Row i of the reduced system becomes bit i of each mask
+    let rank = pivots.len();
+    let mask = |col: usize| {
+        (0..rank)
+            .filter(|&i| matrix[i].get(col))
+            .fold(0u64, |m, i| m | 1 << i)
+    };
+    let (reduced_columns, reduced_target) = if rank <= MAX_DIMENSION {
+        ((0..vars).map(mask).collect(), mask(vars))
+    } else {
+        (Vec::new(), 0)
+    };
+
+    Some(Gf2Solution {
+        particular,
+        nullspace,
+        reduced_columns,
+        reduced_target,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Lcg;
+
+    #[test]
+    fn test_bitvec() {
+        let mut v = BitVec::from_indices(130, &[0, 64, 129, 200]);
+        assert_eq!(v.count_ones(), 3);
+        assert!(v.get(64) && v.get(129) && !v.get(1));
+        v.toggle(64);
+        assert_eq!(v.ones().collect::<Vec<_>>(), vec![0, 129]);
+        v.xor_assign(&BitVec::from_indices(130, &[0, 129]));
+        assert!(v.is_zero());
+    }
+
+    #[test]
+    fn test_solve_and_min_weight() {
+        // Lights .##. with buttons (3) (1,3) (2) (2,3) (0,2) (0,1): best is 2 presses
+        let buttons: Vec<BitVec> = [
+            vec![3],
+            vec![1, 3],
+            vec![2],
+            vec![2, 3],
+            vec![0, 2],
+            vec![0, 1],
+        ]
+        .iter()
+        .map(|b| BitVec::from_indices(4, b))
+        .collect();
+        let target = BitVec::from_indices(4, &[1, 2]);
+
+        let solution = solve(&buttons, &target).unwrap();
+        assert_eq!(solution.dimension(), 2);
+        assert_eq!(solution.min_weight().unwrap().count_ones(), 2);
+    }
+
+    #[test]
+    fn test_no_solution() {
+        // Only one button toggling both lights, but we want just one on
+        let buttons = vec![BitVec::from_indices(2, &[0, 1])];
+        assert!(solve(&buttons, &BitVec::from_indices(2, &[0])).is_none());
+    }
+
+    #[test]
+    fn test_wide_machine() {
+        // 100 lights, button i toggles lights i and i+1: lighting just the two
+        // ends takes every button, far beyond what a 2^100 state BFS could do.
+        let buttons: Vec<BitVec> = (0..99)
+            .map(|i| BitVec::from_indices(100, &[i, i + 1]))
+            .collect();
+        let target = BitVec::from_indices(100, &[0, 99]);
+
+        let solution = solve(&buttons, &target).unwrap();
+        assert_eq!(solution.dimension(), 0);
+        assert_eq!(solution.min_weight().unwrap().count_ones(), 99);
+    }
+
+    #[test]
+    fn test_many_buttons_few_lights() {
+        // 30 buttons over 3 lights: a 27-dimensional solution space, but only
+        // 8 reachable states
+        let buttons: Vec<BitVec> = (0..30).map(|i| BitVec::from_indices(3, &[i % 3])).collect();
+        let solution = solve(&buttons, &BitVec::from_indices(3, &[0, 1, 2])).unwrap();
+        assert_eq!((solution.dimension(), solution.rank()), (27, 3));
+        let x = solution.min_weight().unwrap();
+        assert_eq!(x.count_ones(), 3);
+
+        // The answer really lights the target
+        let mut lights = BitVec::zeros(3);
+        for j in x.ones() {
+            lights.xor_assign(&buttons[j]);
+        }
+        assert_eq!(lights, BitVec::from_indices(3, &[0, 1, 2]));
+
+        // 65 identical one-light buttons: dimension 64, rank 1
+        let buttons = vec![BitVec::from_indices(1, &[0]); 65];
+        let solution = solve(&buttons, &BitVec::from_indices(1, &[0])).unwrap();
+        assert_eq!(solution.min_weight().unwrap().count_ones(), 1);
+    }
+
+    #[test]
+    fn test_search_strategies_agree() {
+        let mut rng = Lcg(7);
+        let mut next = |bound: u64| rng.next(bound);
+        for _ in 0..200 {
+            let (lights, count) = (1 + next(8) as usize, 1 + next(12) as usize);
+            let buttons: Vec<BitVec> = (0..count)
+                .map(|_| {
+                    let on: Vec<usize> = (0..lights).filter(|_| next(2) == 0).collect();
+                    BitVec::from_indices(lights, &on)
+                })
+                .collect();
+            let on: Vec<usize> = (0..lights).filter(|_| next(2) == 0).collect();
+            let Some(solution) = solve(&buttons, &BitVec::from_indices(lights, &on)) else {
+                continue;
+            };
+            assert_eq!(
+                solution.walk_nullspace().count_ones(),
+                solution.search_states().count_ones()
+            );
+        }
+    }
+
+    #[test]
+    fn test_dimension_limit() {
+        // Each of n lights has two identical buttons: dimension n and rank n
+        let solve_pairs = |n: usize| {
+            let buttons: Vec<BitVec> = (0..2 * n)
+                .map(|i| BitVec::from_indices(n, &[i / 2]))
+                .collect();
+            let target = BitVec::from_indices(n, &(0..n).collect::<Vec<_>>());
+            solve(&buttons, &target).unwrap()
+        };
+
+        let solution = solve_pairs(MAX_DIMENSION);
+        assert_eq!(solution.min_weight().unwrap().count_ones(), MAX_DIMENSION);
+
+        let solution = solve_pairs(MAX_DIMENSION + 1);
+        assert_eq!(
+            solution.min_weight(),
+            Err(TooManySolutions {
+                dimension: MAX_DIMENSION + 1,
+                rank: MAX_DIMENSION + 1
+            })
+        );
+    }
+}
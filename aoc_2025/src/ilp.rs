@@ -0,0 +1,544 @@
+use crate::linalg::{Matrix, Rational, gcd};
+
+/// Result of an optimization problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome<T> {
+    Optimal(T),
+    Infeasible,
+    Unbounded,
+    /// Branch and bound hit its node limit holding this solution, which
+    /// isn't proven optimal.
+    Feasible(T),
+    /// Branch and bound hit its node limit before finding any solution.
+    NodeLimit,
+}
+
+/// Subproblems `IntegerProgram::solve` explores before giving up.
+pub const DEFAULT_NODE_LIMIT: usize = 100_000;
+
+/// An optimal point together with its objective value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<T> {
+    pub x: Vec<T>,
+    pub objective: Rational,
+}
+
+/// Minimize c·x subject to A·x = b and x ≥ 0 over the rationals.
+///
+/// Uses a two-phase tableau simplex with Bland's rule, so it is exact and
+/// never cycles.
+pub fn solve_lp(a: &Matrix, b: &[Rational], c: &[Rational]) -> Outcome<Solution<Rational>> {
+    assert_eq!(
+        a.rows(),
+        b.len(),
+        "Right-hand side must have one entry per row"
+    );
+    assert_eq!(
+        a.cols(),
+        c.len(),
+        "Cost vector must have one entry per column"
+    );
+    let (m, n) = (a.rows(), a.cols());
+
+    // Tableau columns: n original variables, m artificials, then the rhs.
+    // Rows are negated where needed so the rhs starts non-negative.
+    let mut rows: Vec<Vec<Rational>> = (0..m)
+        .map(|r| {
+            let flip = b[r] < Rational::ZERO;
+            let mut row = vec![Rational::ZERO; n + m + 1];
+            for (j, val) in row.iter_mut().enumerate().take(n) {
+                *val = if flip { -a.get(r, j) } else { a.get(r, j) };
+            }
+            row[n + r] = Rational::ONE;
+            row[n + m] = if flip { -b[r] } else { b[r] };
+            row
+        })
+        .collect();
+    let mut basis: Vec<usize> = (n..n + m).collect();
+
+    // Phase I: minimize the sum of the artificials to find a feasible basis.
+    let mut phase_one = vec![Rational::ZERO; n + m];
+    phase_one[n..].fill(Rational::ONE);
+    simplex(&mut rows, &mut basis, &phase_one, n + m);
+
+    let infeasibility = basis
+        .iter()
+        .zip(&rows)
+        .filter(|&(&v, _)| v >= n)
+        .fold(Rational::ZERO, |acc, (_, row)| acc + row[n + m]);
+    if !infeasibility.is_zero() {
+        return Outcome::Infeasible;
+    }
+
+    // Drive any remaining (zero-valued) artificials out of the basis; rows
+    // where that's impossible are redundant and can be dropped.
+    let mut r = 0;
+    while r < rows.len() {
+        if basis[r] >= n {
+            if let Some(j) = (0..n).find(|&j| !rows[r][j].is_zero()) {
+                pivot(&mut rows, r, j);
+                basis[r] = j;
+            } else {
+                rows.remove(r);
+                basis.remove(r);
+                continue;
+            }
+        }
+        r += 1;
+    }
+
+    // Phase II: optimize the real objective over the original columns only.
+    if !simplex(&mut rows, &mut basis, c, n) {
+        return Outcome::Unbounded;
+    }
+
+    let mut x = vec![Rational::ZERO; n];
+    for (&v, row) in basis.iter().zip(&rows) {
+        x[v] = row[n + m];
+    }
+    let objective = x
+        .iter()
+        .zip(c)
+        .fold(Rational::ZERO, |acc, (&xi, &ci)| acc + xi * ci);
+
+    Outcome::Optimal(Solution { x, objective })
+}
+
+/// Run simplex iterations on a tableau already in canonical form for `basis`,
+/// only letting columns below `allowed` enter. Returns false if unbounded.
+fn simplex(
+    rows: &mut [Vec<Rational>],
+    basis: &mut [usize],
+    cost: &[Rational],
+    allowed: usize,
+) -> bool {
+    let rhs = rows.first().map_or(0, |r| r.len() - 1);
+
+    loop {
+        // Bland's rule: the lowest-index column with a negative reduced cost enters
+        let entering = (0..allowed).find(|&j| {
+            !basis.contains(&j)
+                && basis
+                    .iter()
+                    .zip(rows.iter())
+                    .fold(cost[j], |acc, (&v, row)| acc - cost[v] * row[j])
+                    < Rational::ZERO
+        });
+        let Some(e) = entering else {
+            return true;
+        };
+
+        // Minimum ratio test, breaking ties on the lowest basic variable
+        let leaving = (0..rows.len())
+            .filter(|&r| rows[r][e] > Rational::ZERO)
+            .min_by(|&p, &q| {
+                (rows[p][rhs] / rows[p][e])
+                    .cmp(&(rows[q][rhs] / rows[q][e]))
+                    .then(basis[p].cmp(&basis[q]))
+            });
+        let Some(l) = leaving else {
+            return false;
+        };
+
+        pivot(rows, l, e);
+        basis[l] = e;
+    }
+}
+
+/// Pivot the tableau on (row, col).
+fn pivot(rows: &mut [Vec<Rational>], row: usize, col: usize) {
+    let pivot_value = rows[row][col];
+    for val in rows[row].iter_mut() {
+        *val = *val / pivot_value;
+    }
+
+    let pivot_row = rows[row].clone();
+    for (r, other) in rows.iter_mut().enumerate() {
+        let factor = other[col];
+        if r != row && !factor.is_zero() {
+            for (val, &p) in other.iter_mut().zip(&pivot_row) {
+                *val = *val - factor * p;
+            }
+        }
+    }
+}
+
+/// Minimize c·x subject to A·x = b, x ≥ 0 and x integer, optionally with
+/// per-variable upper bounds.
+///
+/// Solved by depth-first branch and bound on the exact LP relaxation.
+/// Supplying upper bounds keeps the search finite even when the relaxation
+/// alone wouldn't bound a variable; without them the node limit stops it.
+#[derive(Debug, Clone)]
+pub struct IntegerProgram {
+    a: Matrix,
+    b: Vec<Rational>,
+    c: Vec<Rational>,
+    upper: Vec<Option<i128>>,
+    integral_costs: bool,
+    node_limit: usize,
+}
+
+impl IntegerProgram {
+    pub fn new(a: Matrix, b: Vec<Rational>, c: Vec<Rational>) -> Self {
+        assert_eq!(
+            a.rows(),
+            b.len(),
+            "Right-hand side must have one entry per row"
+        );
+        assert_eq!(
+            a.cols(),
+            c.len(),
+            "Cost vector must have one entry per column"
+        );
+        let upper = vec![None; a.cols()];
+        let integral_costs = c.iter().all(Rational::is_integer);
+        IntegerProgram {
+            a,
+            b,
+            c,
+            upper,
+            integral_costs,
+            node_limit: DEFAULT_NODE_LIMIT,
+        }
+    }
+
+    /// Restrict each variable to at most the given value (`None` for no bound).
+    pub fn with_upper_bounds(mut self, upper: Vec<Option<i128>>) -> Self {
+        assert_eq!(upper.len(), self.a.cols());
+        self.upper = upper;
+        self
+    }
+
+    /// Explore at most `limit` subproblems before returning the best solution
+    /// so far as `Outcome::Feasible`, or `Outcome::NodeLimit` if there's none.
+    pub fn with_node_limit(mut self, limit: usize) -> Self {
+        self.node_limit = limit;
+        self
+    }
+
+    pub fn solve(&self) -> Outcome<Solution<i128>> {
+        if !self.rows_divisible() {
+            return Outcome::Infeasible;
+        }
+
+        let n = self.a.cols();
+        let mut best: Option<Solution<i128>> = None;
+        let mut stack = vec![(vec![0; n], self.upper.clone())];
+        let mut nodes = 0;
+
+        while let Some((lower, upper)) = stack.pop() {
+            if nodes == self.node_limit {
+                return match best {
+                    Some(solution) => Outcome::Feasible(solution),
+                    None => Outcome::NodeLimit,
+                };
+            }
+            nodes += 1;
+            match self.branch(lower, upper, &mut best) {
+                Branch::Done => {}
+                Branch::Unbounded => return Outcome::Unbounded,
+                // Pushed in reverse so the nearer side is explored first
+                Branch::Split(first, second) => stack.extend([second, first]),
+            }
+        }
+
+        match best {
+            Some(solution) => Outcome::Optimal(solution),
+            None => Outcome::Infeasible,
+        }
+    }
+
+    /// A row whose integer coefficients share a factor that doesn't divide its
+    /// right-hand side has no integer solution, like 2x - 2y = 1. Branching
+    /// alone would never prove that when the variables are unbounded.
+    fn rows_divisible(&self) -> bool {
+        (0..self.a.rows()).all(|r| {
+            let mut coeffs = (0..self.a.cols()).map(|c| self.a.get(r, c).to_integer());
+            let Some(common) = coeffs.try_fold(0, |g, v| Some(gcd(g, v?))) else {
+                return true;
+            };
+            match self.b[r].to_integer() {
+                Some(rhs) if common != 0 => rhs % common == 0,
+                Some(rhs) => rhs == 0,
+                None => common == 0,
+            }
+        })
+    }
+
+    /// Solve the relaxation of the subproblem lower ≤ x ≤ upper, updating the
+    /// incumbent, and return the two subproblems to explore next if it's fractional.
+    fn branch(
+        &self,
+        lower: Vec<i128>,
+        upper: Vec<Option<i128>>,
+        best: &mut Option<Solution<i128>>,
+    ) -> Branch {
+        let Some(relaxation) = self.relax(&lower, &upper) else {
+            return Branch::Done;
+        };
+        let Outcome::Optimal(lp) = relaxation else {
+            return Branch::Unbounded;
+        };
+
+        // Prune if the relaxation can't beat what we already have. With integer
+        // costs every integer point has an integer objective, so round up first.
+        let bound = if self.integral_costs {
+            Rational::from(ceil(lp.objective))
+        } else {
+            lp.objective
+        };
+        if best.as_ref().is_some_and(|b| bound >= b.objective) {
+            return Branch::Done;
+        }
+
+        // Branch on the most fractional variable
+        let half = Rational::new(1, 2);
+        let fractional =
+            lp.x.iter()
+                .enumerate()
+                .filter(|(_, v)| !v.is_integer())
+                .min_by_key(|&(_, &v)| (v - Rational::from(floor(v)) - half).abs());
+        let Some((j, &value)) = fractional else {
+            *best = Some(Solution {
+                x: lp.x.iter().map(|v| v.to_integer().unwrap()).collect(),
+                objective: lp.objective,
+            });
+            return Branch::Done;
+        };
+
+        let floor = floor(value);
+        let mut down = upper.clone();
+        down[j] = Some(floor);
+        let mut up = lower.clone();
+        up[j] = floor + 1;
+
+        // Try the side nearer the relaxation's value first, since that's
+        // usually where a good incumbent is found quickly.
+        let mut sides = [(lower, down), (up, upper)];
+        if value - Rational::from(floor) >= half {
+            sides.swap(0, 1);
+        }
+        let [first, second] = sides;
+        Branch::Split(first, second)
+    }
+
+    /// Solve the LP relaxation with bounds. `None` means infeasible.
+    ///
+    /// Upper bounds are added as constraint rows lazily: most are implied by
+    /// A·x = b, so we only pay for the ones the relaxation actually violates.
+    fn relax(&self, lower: &[i128], upper: &[Option<i128>]) -> Option<Outcome<Solution<Rational>>> {
+        if lower
+            .iter()
+            .zip(upper)
+            .any(|(&lo, hi)| hi.is_some_and(|h| h < lo))
+        {
+            return None;
+        }
+
+        let mut active = vec![false; upper.len()];
+        loop {
+            let outcome = self.relax_with(lower, upper, &active)?;
+
+            let violated: Vec<usize> = match &outcome {
+                Outcome::Optimal(lp) => (0..upper.len())
+                    .filter(|&j| upper[j].is_some_and(|h| lp.x[j] > Rational::from(h)))
+                    .collect(),
+                // A missing bound may be what makes it unbounded
+                _ => (0..upper.len())
+                    .filter(|&j| upper[j].is_some() && !active[j])
+                    .collect(),
+            };
+            if violated.is_empty() {
+                return Some(outcome);
+            }
+            for j in violated {
+                active[j] = true;
+            }
+        }
+    }
+
+    /// Solve the LP relaxation with the lower bounds and the `active` upper bounds.
+    fn relax_with(
+        &self,
+        lower: &[i128],
+        upper: &[Option<i128>],
+        active: &[bool],
+    ) -> Option<Outcome<Solution<Rational>>> {
+        let (m, n) = (self.a.rows(), self.a.cols());
+
+        // Shift x = lower + y so y ≥ 0, and add a slack row y_j + s = hi - lo
+        // for every active upper bound.
+        let bounded: Vec<(usize, i128)> = upper
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| active[j])
+            .filter_map(|(j, hi)| hi.map(|h| (j, h - lower[j])))
+            .collect();
+        let cols = n + bounded.len();
+
+        let mut a = Matrix::zeros(m + bounded.len(), cols);
+        let mut b = Vec::with_capacity(m + bounded.len());
+        for r in 0..m {
+            let mut rhs = self.b[r];
+            for (j, &lo) in lower.iter().enumerate() {
+                let coeff = self.a.get(r, j);
+                a.set(r, j, coeff);
+                rhs = rhs - coeff * Rational::from(lo);
+            }
+            b.push(rhs);
+        }
+        for (k, &(j, width)) in bounded.iter().enumerate() {
+            a.set(m + k, j, 1i128);
+            a.set(m + k, n + k, 1i128);
+            b.push(Rational::from(width));
+        }
+
+        let mut c = self.c.clone();
+        c.resize(cols, Rational::ZERO);
+
+        let offset = self
+            .c
+            .iter()
+            .zip(lower)
+            .fold(Rational::ZERO, |acc, (&ci, &lo)| {
+                acc + ci * Rational::from(lo)
+            });
+
+        match solve_lp(&a, &b, &c) {
+            Outcome::Optimal(lp) => Some(Outcome::Optimal(Solution {
+                x: lp.x[..n]
+                    .iter()
+                    .zip(lower)
+                    .map(|(&y, &lo)| y + Rational::from(lo))
+                    .collect(),
+                objective: lp.objective + offset,
+            })),
+            Outcome::Infeasible => None,
+            Outcome::Unbounded => Some(Outcome::Unbounded),
+            Outcome::Feasible(_) | Outcome::NodeLimit => unreachable!("simplex always finishes"),
+        }
+    }
+}
+
+/// What one branch-and-bound node leaves to do.
+enum Branch {
+    /// Pruned, infeasible, or a new incumbent: nothing below this node.
+    Done,
+    Unbounded,
+    /// Subproblems (lower, upper) to explore, the more promising first.
+    Split(
+        (Vec<i128>, Vec<Option<i128>>),
+        (Vec<i128>, Vec<Option<i128>>),
+    ),
+}
+
+/// Largest integer not greater than `q`.
+fn floor(q: Rational) -> i128 {
+    q.numer().div_euclid(q.denom())
+}
+
+/// Smallest integer not less than `q`.
+fn ceil(q: Rational) -> i128 {
+    -floor(-q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints(values: &[i128]) -> Vec<Rational> {
+        values.iter().map(|&v| Rational::from(v)).collect()
+    }
+
+    #[test]
+    fn test_lp() {
+        // min -x - y  s.t.  x + 2y + s = 4,  3x + y + t = 6
+        let a = Matrix::from_rows(vec![vec![1i64, 2, 1, 0], vec![3, 1, 0, 1]]);
+        let Outcome::Optimal(lp) = solve_lp(&a, &ints(&[4, 6]), &ints(&[-1, -1, 0, 0])) else {
+            panic!("expected an optimum");
+        };
+        assert_eq!(lp.objective, Rational::new(-14, 5));
+        assert_eq!(lp.x[..2], [Rational::new(8, 5), Rational::new(6, 5)]);
+
+        // x - y = 0 with min -x is unbounded
+        let a = Matrix::from_rows(vec![vec![1i64, -1]]);
+        assert_eq!(
+            solve_lp(&a, &ints(&[0]), &ints(&[-1, 0])),
+            Outcome::Unbounded
+        );
+
+        // x + y = -1 has no non-negative solution
+        let a = Matrix::from_rows(vec![vec![1i64, 1]]);
+        assert_eq!(
+            solve_lp(&a, &ints(&[-1]), &ints(&[1, 1])),
+            Outcome::Infeasible
+        );
+    }
+
+    #[test]
+    fn test_integer_program() {
+        // min x + y  s.t.  2x + 2y = 3 has a rational but no integer solution
+        let a = Matrix::from_rows(vec![vec![2i64, 2]]);
+        let ip = IntegerProgram::new(a, ints(&[3]), ints(&[1, 1]));
+        assert_eq!(ip.solve(), Outcome::Infeasible);
+
+        // min x + y  s.t.  3x + 5y = 19  ->  x = 3, y = 2
+        let a = Matrix::from_rows(vec![vec![3i64, 5]]);
+        let ip = IntegerProgram::new(a, ints(&[19]), ints(&[1, 1]));
+        assert_eq!(
+            ip.solve(),
+            Outcome::Optimal(Solution {
+                x: vec![3, 2],
+                objective: Rational::from(5i128),
+            })
+        );
+    }
+
+    #[test]
+    fn test_unbounded_variables_terminate() {
+        // min x + y  s.t.  2x - 2y = 1: the gcd of the row rules out integers
+        let a = Matrix::from_rows(vec![vec![2i64, -2]]);
+        let ip = IntegerProgram::new(a, ints(&[1]), ints(&[1, 1]));
+        assert_eq!(ip.solve(), Outcome::Infeasible);
+
+        // x = 2y and x = 2z + 1 need x both even and odd, but each row alone is
+        // fine and nothing bounds x, so branching would go on forever
+        let a = Matrix::from_rows(vec![vec![1i64, -2, 0], vec![1, 0, -2]]);
+        let ip = IntegerProgram::new(a, ints(&[0, 1]), ints(&[1, 1, 1])).with_node_limit(500);
+        assert_eq!(ip.solve(), Outcome::NodeLimit);
+    }
+
+    #[test]
+    fn test_upper_bounds() {
+        // max x (cost -x)  s.t.  x - y = 2 with y ≤ 1 and x ≥ 0
+        let a = Matrix::from_rows(vec![vec![1i64, -1]]);
+        let ip = IntegerProgram::new(a, ints(&[2]), ints(&[-1, 0]))
+            .with_upper_bounds(vec![None, Some(1)]);
+        let Outcome::Optimal(solution) = ip.solve() else {
+            panic!("expected an optimum");
+        };
+        assert_eq!(solution.x, vec![3, 1]);
+    }
+
+    #[test]
+    fn test_node_limit_keeps_incumbent() {
+        // min x + y + z  s.t.  3x + 5y + 7z = 101: an integer point turns up
+        // a few nodes before it can be proven optimal
+        let a = Matrix::from_rows(vec![vec![3i64, 5, 7]]);
+        let ip = IntegerProgram::new(a, ints(&[101]), ints(&[1, 1, 1]));
+        assert_eq!(ip.clone().with_node_limit(1).solve(), Outcome::NodeLimit);
+
+        let Outcome::Feasible(incumbent) = ip.clone().with_node_limit(5).solve() else {
+            panic!("expected an unproven solution");
+        };
+        assert_eq!(
+            3 * incumbent.x[0] + 5 * incumbent.x[1] + 7 * incumbent.x[2],
+            101
+        );
+        let Outcome::Optimal(optimum) = ip.solve() else {
+            panic!("expected an optimum");
+        };
+        assert!(optimum.objective <= incumbent.objective);
+    }
+}
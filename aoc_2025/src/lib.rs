@@ -1,24 +1,47 @@
+use std::env;
 use std::fs;
 use std::path::Path;
 
+use parse::Sections;
+
+pub mod bigint;
 pub mod days;
+pub mod gf2;
+pub mod ilp;
+pub mod linalg;
+pub mod parse;
+#[cfg(test)]
+mod rng;
 
-/// Reads lines from the given input txt file.
-pub fn read_input(name: &str) -> Vec<String> {
+/// Reads the given input txt file exactly as it is on disk.
+fn read_file(name: &str) -> String {
     let path = format!("../input/2025/{}.txt", name);
     fs::read_to_string(Path::new(&path))
-        .expect(&format!("Could not read file: {}", path))
-        .trim()
-        .lines()
-        .map(String::from)
-        .collect()
+        .unwrap_or_else(|_| panic!("Could not read file: {}", path))
+}
+
+/// Reads lines from the given input txt file.
+pub fn read_input(name: &str) -> Vec<String> {
+    read_file(name).trim().lines().map(String::from).collect()
 }
 
 /// Reads the entire input file as a single string.
 pub fn read_input_raw(name: &str) -> String {
-    let path = format!("../input/2025/{}.txt", name);
-    fs::read_to_string(Path::new(&path))
-        .expect(&format!("Could not read file: {}", path))
-        .trim()
-        .to_string()
+    read_file(name).trim().to_string()
+}
+
+/// Writes `contents()` to `file` in the directory named by `AOC_DEBUG_DIR`.
+/// Does nothing (and never builds the contents) when the variable is unset.
+pub fn write_debug(file: &str, contents: impl FnOnce() -> String) {
+    if let Ok(dir) = env::var("AOC_DEBUG_DIR") {
+        let path = Path::new(&dir).join(file);
+        fs::write(&path, contents())
+            .unwrap_or_else(|e| panic!("Could not write {}: {}", path.display(), e));
+    }
+}
+
+/// Reads the input file as blank-line-separated sections. The text isn't
+/// trimmed, so line numbers in parse errors match the file.
+pub fn read_sections(name: &str) -> Sections {
+    Sections::new(read_file(name))
 }
@@ -0,0 +1,365 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Greatest common divisor of two integers (always non-negative).
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a as i128
+}
+
+/// An exact fraction backed by `i128`, always kept in lowest terms with a
+/// positive denominator. Arithmetic panics on overflow instead of wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "Rational with zero denominator");
+        let g = gcd(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        Rational {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// The value as an integer, if it is one.
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn abs(&self) -> Self {
+        Rational {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+
+    pub fn recip(&self) -> Self {
+        Rational::new(self.den, self.num)
+    }
+}
+
+fn checked(value: Option<i128>) -> i128 {
+    value.expect("Rational arithmetic overflowed i128")
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational { num: n, den: 1 }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Rational::from(n as i128)
+    }
+}
+
+impl From<usize> for Rational {
+    fn from(n: usize) -> Self {
+        Rational::from(n as i128)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Rational {
+        // Scale through the lcm of the denominators to keep intermediates small
+        let g = gcd(self.den, rhs.den);
+        let lhs_scale = rhs.den / g;
+        let rhs_scale = self.den / g;
+        let num = checked(
+            checked(self.num.checked_mul(lhs_scale))
+                .checked_add(checked(rhs.num.checked_mul(rhs_scale))),
+        );
+        Rational::new(num, checked(self.den.checked_mul(lhs_scale)))
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Rational {
+        self + (-rhs)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Rational {
+        // Cross-cancel first so the products stay in range as long as possible
+        let g1 = gcd(self.num, rhs.den);
+        let g2 = gcd(rhs.num, self.den);
+        let num = checked((self.num / g1).checked_mul(rhs.num / g2));
+        let den = checked((self.den / g2).checked_mul(rhs.den / g1));
+        Rational::new(num, den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Rational) -> Rational {
+        self.mul(rhs.recip())
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are positive, so cross-multiplying keeps the order
+        let lhs = checked(self.num.checked_mul(other.den));
+        let rhs = checked(other.num.checked_mul(self.den));
+        lhs.cmp(&rhs)
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// A dense matrix of exact rationals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<Vec<Rational>>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![vec![Rational::ZERO; cols]; rows],
+        }
+    }
+
+    /// Build a matrix from rows of anything convertible to a `Rational`.
+    /// Panics if the rows have different lengths.
+    pub fn from_rows<T: Into<Rational>>(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        let data: Vec<Vec<Rational>> = rows
+            .into_iter()
+            .map(|row| {
+                assert_eq!(row.len(), cols, "Matrix rows must have equal length");
+                row.into_iter().map(Into::into).collect()
+            })
+            .collect();
+        Matrix {
+            rows: data.len(),
+            cols,
+            data,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Rational {
+        self.data[row][col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: impl Into<Rational>) {
+        self.data[row][col] = value.into();
+    }
+
+    pub fn row(&self, row: usize) -> &[Rational] {
+        &self.data[row]
+    }
+
+    /// Reduced row echelon form, along with its pivot and free columns.
+    pub fn rref(&self) -> Rref {
+        let mut matrix = self.clone();
+        let pivots = matrix.eliminate();
+        let free = (0..self.cols).filter(|c| !pivots.contains(c)).collect();
+        Rref {
+            matrix,
+            pivots,
+            free,
+        }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rref().pivots.len()
+    }
+
+    /// A basis for the nullspace (all x with A·x = 0), one vector per free column.
+    pub fn nullspace(&self) -> Vec<Vec<Rational>> {
+        self.rref().nullspace()
+    }
+
+    /// Gauss-Jordan elimination in place. Returns the pivot columns; pivot
+    /// row `i` holds pivot column `pivots[i]`.
+    fn eliminate(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        let mut pivot = 0;
+
+        for col in 0..self.cols {
+            if pivot == self.rows {
+                break;
+            }
+
+            // Any non-zero entry works as a pivot since arithmetic is exact
+            let Some(found) = (pivot..self.rows).find(|&r| !self.data[r][col].is_zero()) else {
+                continue;
+            };
+            self.data.swap(pivot, found);
+
+            // Normalize pivot row.
+            let pivot_value = self.data[pivot][col];
+            for val in &mut self.data[pivot][col..] {
+                *val = *val / pivot_value;
+            }
+
+            // Eliminate this column in all other rows.
+            let pivot_row = self.data[pivot].clone();
+            for r in 0..self.rows {
+                let factor = self.data[r][col];
+                if r != pivot && !factor.is_zero() {
+                    for (val, &p) in self.data[r][col..].iter_mut().zip(&pivot_row[col..]) {
+                        *val = *val - factor * p;
+                    }
+                }
+            }
+
+            pivots.push(col);
+            pivot += 1;
+        }
+
+        pivots
+    }
+}
+
+/// A matrix in reduced row echelon form.
+#[derive(Debug, Clone)]
+pub struct Rref {
+    pub matrix: Matrix,
+    /// Columns holding a leading one, in row order.
+    pub pivots: Vec<usize>,
+    /// Columns without a pivot, i.e. the free variables.
+    pub free: Vec<usize>,
+}
+
+impl Rref {
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// A basis for the nullspace, one vector per free column.
+    pub fn nullspace(&self) -> Vec<Vec<Rational>> {
+        self.free
+            .iter()
+            .map(|&f| {
+                let mut v = vec![Rational::ZERO; self.matrix.cols];
+                v[f] = Rational::ONE;
+                for (row, &p) in self.pivots.iter().enumerate() {
+                    v[p] = -self.matrix.data[row][f];
+                }
+                v
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn q(num: i128, den: i128) -> Rational {
+        Rational::new(num, den)
+    }
+
+    #[test]
+    fn test_rational_arithmetic() {
+        assert_eq!(q(2, 4), q(1, 2));
+        assert_eq!(q(1, -3), q(-1, 3));
+        assert_eq!(q(1, 2) + q(1, 3), q(5, 6));
+        assert_eq!(q(1, 2) - q(3, 4), q(-1, 4));
+        assert_eq!(q(2, 3) * q(9, 4), q(3, 2));
+        assert_eq!(q(2, 3) / q(4, 9), q(3, 2));
+        assert!(q(1, 3) < q(1, 2));
+        assert_eq!(q(6, 3).to_integer(), Some(2));
+        assert_eq!(q(7, 3).to_integer(), None);
+        assert_eq!(q(-7, 3).to_string(), "-7/3");
+    }
+
+    #[test]
+    fn test_large_coefficients_stay_exact() {
+        // 1e15 * (1/3) * 3 would drift in f64 but must round-trip exactly here
+        let big = Rational::from(1_000_000_000_000_001i128);
+        assert_eq!(big * q(1, 3) * Rational::from(3i128), big);
+    }
+
+    #[test]
+    fn test_rref_rank_nullspace() {
+        let m = Matrix::from_rows(vec![vec![1i64, 2, 1], vec![2, 4, 0], vec![3, 6, 1]]);
+        let rref = m.rref();
+        assert_eq!(rref.pivots, vec![0, 2]);
+        assert_eq!(rref.free, vec![1]);
+        assert_eq!(m.rank(), 2);
+
+        let basis = m.nullspace();
+        assert_eq!(basis, vec![vec![q(-2, 1), q(1, 1), q(0, 1)]]);
+        for r in 0..m.rows() {
+            let dot = (0..m.cols()).fold(Rational::ZERO, |acc, c| acc + m.get(r, c) * basis[0][c]);
+            assert!(dot.is_zero());
+        }
+    }
+}
@@ -0,0 +1,509 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// A parse failure with the (1-based) line and column where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Extract all signed integers from a line, ignoring everything in between.
+///
+/// A `-` only counts as a sign when it isn't directly preceded by a digit,
+/// so ranges like `3-5` give `[3, 5]` rather than `[3, -5]`.
+/// Fails at the first number that doesn't fit in `T`, including negative
+/// numbers when `T` is unsigned.
+pub fn ints<T: FromStr>(line: &str) -> Result<Vec<T>, ParseError> {
+    let mut cursor = Cursor::new(line);
+    let mut values = Vec::new();
+
+    while let Some(ch) = cursor.peek() {
+        let is_sign = ch == '-'
+            && !cursor.prev().is_some_and(|p| p.is_ascii_digit())
+            && cursor.peek_nth(1).is_some_and(|n| n.is_ascii_digit());
+
+        if ch.is_ascii_digit() || is_sign {
+            values.push(cursor.int()?);
+        } else {
+            cursor.bump();
+        }
+    }
+
+    Ok(values)
+}
+
+/// Split the input into sections separated by blank (or whitespace-only) lines.
+/// Leading, trailing and repeated blank lines never produce empty sections.
+pub fn split_sections(input: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+    let mut offset = 0;
+
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(s) = start.take() {
+                sections.push(&input[s..end]);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + line.trim_end_matches(['\r', '\n']).len();
+        }
+        offset += line.len();
+    }
+
+    if let Some(s) = start {
+        sections.push(&input[s..end]);
+    }
+
+    sections
+}
+
+/// Parse a string like `3-5` or `10,20` into a pair split on the first `sep`.
+/// Whitespace around either side is ignored.
+pub fn parse_pair<A: FromStr, B: FromStr>(s: &str, sep: &str) -> Result<(A, B), ParseError> {
+    let mut cursor = Cursor::new(s);
+    cursor.skip_ws();
+    let left = cursor.until(sep)?;
+    let a = cursor.value_at(left)?;
+    cursor.tag(sep)?;
+    cursor.skip_ws();
+    let right = cursor.rest();
+    let b = cursor.value_at(right)?;
+    Ok((a, b))
+}
+
+/// Parse every non-empty line with `f`, numbering lines from 1 so errors point at
+/// the right place in the input file.
+pub fn parse_lines<'a, T, S, F>(lines: &'a [S], mut f: F) -> Result<Vec<T>, ParseError>
+where
+    S: AsRef<str>,
+    F: FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+{
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.as_ref().is_empty())
+        .map(|(i, line)| {
+            let mut cursor = Cursor::new(line.as_ref()).with_line(i + 1);
+            let value = f(&mut cursor)?;
+            cursor.skip_ws();
+            cursor.end()?;
+            Ok(value)
+        })
+        .collect()
+}
+
+/// Input split into blank-line-separated sections, for inputs with a header/body
+/// structure. Each line remembers its line number so parse errors point into the
+/// original file. Leading, trailing and repeated blank (or whitespace-only) lines
+/// never produce empty sections.
+#[derive(Debug, Clone)]
+pub struct Sections {
+    text: String,
+    sections: Vec<Vec<(usize, Range<usize>)>>,
+}
+
+impl Sections {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let mut sections = Vec::new();
+        let mut current = Vec::new();
+        let mut offset = 0;
+
+        for (i, line) in text.split_inclusive('\n').enumerate() {
+            let content = line.trim_end_matches(['\r', '\n']);
+            if content.trim().is_empty() {
+                if !current.is_empty() {
+                    sections.push(std::mem::take(&mut current));
+                }
+            } else {
+                current.push((i + 1, offset..offset + content.len()));
+            }
+            offset += line.len();
+        }
+
+        if !current.is_empty() {
+            sections.push(current);
+        }
+
+        Sections { text, sections }
+    }
+
+    /// Number of sections.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    /// The lines of section `index`, if it exists.
+    pub fn get(&self, index: usize) -> Option<Vec<&str>> {
+        self.sections
+            .get(index)
+            .map(|lines| lines.iter().map(|(_, r)| &self.text[r.clone()]).collect())
+    }
+
+    /// The lines of section `index`, or an error if the input has too few sections.
+    pub fn section(&self, index: usize) -> Result<Vec<&str>, ParseError> {
+        self.get(index).ok_or_else(|| self.missing(index))
+    }
+
+    /// Iterate over all sections in order.
+    pub fn iter(&self) -> impl Iterator<Item = Vec<&str>> {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+
+    /// Parse each line of section `index` as a `T`.
+    pub fn parse<T: FromStr>(&self, index: usize) -> Result<Vec<T>, ParseError> {
+        self.parse_iter(index)?.collect()
+    }
+
+    /// Parse the lines of section `index` as `T`s one at a time, so a long
+    /// section never has to be held in memory as values.
+    pub fn parse_iter<T: FromStr>(
+        &self,
+        index: usize,
+    ) -> Result<impl Iterator<Item = Result<T, ParseError>> + '_, ParseError> {
+        let lines = self
+            .sections
+            .get(index)
+            .ok_or_else(|| self.missing(index))?;
+        Ok(lines.iter().map(|(line, r)| {
+            let cursor = Cursor::new(&self.text[r.clone()]).with_line(*line);
+            cursor.value_at(cursor.rest())
+        }))
+    }
+
+    /// Parse each line of section `index` with `f`, which must consume the whole line.
+    pub fn parse_with<'a, T>(
+        &'a self,
+        index: usize,
+        mut f: impl FnMut(&mut Cursor<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let lines = self
+            .sections
+            .get(index)
+            .ok_or_else(|| self.missing(index))?;
+        lines
+            .iter()
+            .map(|(line, r)| {
+                let mut cursor = Cursor::new(&self.text[r.clone()]).with_line(*line);
+                let value = f(&mut cursor)?;
+                cursor.skip_ws().end()?;
+                Ok(value)
+            })
+            .collect()
+    }
+
+    fn missing(&self, index: usize) -> ParseError {
+        ParseError {
+            line: self.text.lines().count() + 1,
+            column: 1,
+            message: format!("expected section {}, found {}", index + 1, self.len()),
+        }
+    }
+}
+
+/// A tiny hand-rolled parser combinator over a string slice.
+///
+/// Each method consumes input from the front and reports errors with the
+/// line and column of the current position.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Cursor {
+            input,
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    /// Set the line number of the start of the input, for error reporting.
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// The line number of the current position.
+    pub fn line(&self) -> usize {
+        self.line + self.input[..self.pos].matches('\n').count()
+    }
+
+    /// The unconsumed part of the input.
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn prev(&self) -> Option<char> {
+        self.input[..self.pos].chars().next_back()
+    }
+
+    /// Consume and return the next char.
+    pub fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    /// Build an error pointing at the current position.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> ParseError {
+        let before = &self.input[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: self.line + before.matches('\n').count(),
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+
+    /// Skip spaces and tabs (but not newlines).
+    pub fn skip_ws(&mut self) -> &mut Self {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        self
+    }
+
+    /// Consume characters while `pred` holds and return them.
+    pub fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(&pred) {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    /// Consume everything up to (but not including) `sep`.
+    pub fn until(&mut self, sep: &str) -> Result<&'a str, ParseError> {
+        let Some(len) = self.rest().find(sep) else {
+            return Err(self.error(format!("expected '{}'", sep)));
+        };
+        let start = self.pos;
+        self.pos += len;
+        Ok(&self.input[start..self.pos])
+    }
+
+    /// Consume `tag` exactly or fail.
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if self.rest().starts_with(tag) {
+            self.pos += tag.len();
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", tag)))
+        }
+    }
+
+    /// Parse an optionally signed integer.
+    pub fn int<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.pos;
+        if self.peek() == Some('-') || self.peek() == Some('+') {
+            self.bump();
+        }
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            self.pos = start;
+            return Err(self.error("expected an integer"));
+        }
+        let text = &self.input[start..self.pos];
+        text.parse()
+            .map_err(|_| self.error_at(start, format!("invalid integer '{}'", text)))
+    }
+
+    /// Parse `open`, then `f`, then `close`.
+    pub fn delimited<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        f: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        self.tag(open)?;
+        let value = f(self)?;
+        self.tag(close)?;
+        Ok(value)
+    }
+
+    /// Parse one or more `f` separated by `sep`.
+    pub fn separated<T>(
+        &mut self,
+        sep: &str,
+        mut f: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut values = vec![f(self)?];
+        while self.rest().starts_with(sep) {
+            self.pos += sep.len();
+            values.push(f(self)?);
+        }
+        Ok(values)
+    }
+
+    /// Fail unless all input has been consumed.
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(format!("unexpected '{}'", self.rest())))
+        }
+    }
+
+    /// Parse a slice previously taken from this cursor, reporting errors at its position.
+    fn value_at<T: FromStr>(&self, slice: &'a str) -> Result<T, ParseError> {
+        let start = slice.as_ptr() as usize - self.input.as_ptr() as usize;
+        let trimmed = slice.trim();
+        let offset = start + (slice.len() - slice.trim_start().len());
+        trimmed
+            .parse()
+            .map_err(|_| self.error_at(offset, format!("invalid value '{}'", trimmed)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints::<i64>("x=-3, y=12 z=+4").unwrap(), vec![-3, 12, 4]);
+        assert_eq!(ints::<u64>("3-5").unwrap(), vec![3, 5]);
+        assert_eq!(ints::<i32>("162,817,812").unwrap(), vec![162, 817, 812]);
+        assert_eq!(ints::<i32>("no numbers - here").unwrap(), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_ints_out_of_range() {
+        let err = ints::<u64>("x -3").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+        assert!(err.message.contains("'-3'"));
+
+        let err = ints::<i32>("1, 99999999999").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 4));
+    }
+
+    #[test]
+    fn test_split_sections() {
+        let input = "\n3-5\n10-14\n\n\n1\r\n5\r\n  \n";
+        assert_eq!(split_sections(input), vec!["3-5\n10-14", "1\r\n5"]);
+        assert!(split_sections("").is_empty());
+    }
+
+    #[test]
+    fn test_sections_blank_lines() {
+        let sections = Sections::new("\n3-5\n10-14\n\n\n1\r\n5\r\n  \n");
+        let all: Vec<_> = sections.iter().collect();
+        assert_eq!(all, vec![vec!["3-5", "10-14"], vec!["1", "5"]]);
+        let err = sections.parse::<u64>(0).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(Sections::new("").is_empty());
+    }
+
+    #[test]
+    fn test_sections() {
+        let sections = Sections::new("3-5\n10-14\n\n1\n5\nx\n");
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections.section(0).unwrap(), vec!["3-5", "10-14"]);
+        assert_eq!(sections.iter().count(), 2);
+
+        let ranges = sections
+            .parse_with(0, |c| {
+                let start: u64 = c.int()?;
+                c.tag("-")?;
+                Ok((start, c.int::<u64>()?))
+            })
+            .unwrap();
+        assert_eq!(ranges, vec![(3, 5), (10, 14)]);
+
+        let err = sections.parse::<u64>(1).unwrap_err();
+        assert_eq!((err.line, err.column), (6, 1));
+
+        // Lazily, the lines before the bad one still come through
+        let mut ids = sections.parse_iter::<u64>(1).unwrap();
+        assert_eq!(ids.next(), Some(Ok(1)));
+        assert_eq!(ids.next(), Some(Ok(5)));
+        assert_eq!(ids.next().unwrap().unwrap_err().line, 6);
+        assert!(sections.parse_iter::<u64>(2).is_err());
+
+        let err = sections.section(2).unwrap_err();
+        assert_eq!(err.message, "expected section 3, found 2");
+    }
+
+    #[test]
+    fn test_parse_pair() {
+        assert_eq!(parse_pair::<u64, u64>("3-5", "-"), Ok((3, 5)));
+        assert_eq!(
+            parse_pair::<i64, String>(" 7 , abc", ","),
+            Ok((7, "abc".to_string()))
+        );
+
+        let err = parse_pair::<u64, u64>("12-x4", "-").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 4));
+
+        let err = parse_pair::<u64, u64>("12", "-").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
+
+    #[test]
+    fn test_cursor_combinators() {
+        let mut c = Cursor::new("(0,1,2) {3,5}");
+        let button: Vec<usize> = c
+            .delimited("(", ")", |c| c.separated(",", Cursor::int))
+            .unwrap();
+        c.skip_ws();
+        let joltages: Vec<usize> = c
+            .delimited("{", "}", |c| c.separated(",", Cursor::int))
+            .unwrap();
+        assert_eq!(button, vec![0, 1, 2]);
+        assert_eq!(joltages, vec![3, 5]);
+        assert!(c.end().is_ok());
+
+        let mut c = Cursor::new("a\nb").with_line(4);
+        assert_eq!(c.line(), 4);
+        c.until("\n").unwrap();
+        c.tag("\n").unwrap();
+        assert_eq!(c.line(), 5);
+    }
+
+    #[test]
+    fn test_parse_lines_reports_position() {
+        let lines = ["1,2", "", "3,x"];
+        let err = parse_lines(&lines, |c| c.separated(",", Cursor::int::<u32>)).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+        assert_eq!(err.to_string(), "line 3, column 3: expected an integer");
+    }
+}
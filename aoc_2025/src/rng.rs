@@ -0,0 +1,16 @@
+/// Small deterministic LCG for generated test and bench inputs, so runs are
+/// comparable without extra dependencies. Only built for tests; the benches
+/// share their own in `benches/common`.
+#[derive(Debug, Clone)]
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    /// The next value in `0..bound`.
+    pub fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}